@@ -0,0 +1,96 @@
+//! Keeps the AccessKit tree of egui to find widgets in it.
+
+use std::collections::HashMap;
+
+use egui::accesskit::{Node, NodeId, Role, TreeUpdate};
+
+/// The nodes of the last AccessKit tree of egui.
+#[derive(Default)]
+pub(crate) struct AccessTree {
+    nodes: HashMap<NodeId, Node>,
+    root: Option<NodeId>,
+    focus: Option<NodeId>,
+}
+
+impl AccessTree {
+    pub(crate) fn update(&mut self, update: &TreeUpdate) {
+        if let Some(tree) = &update.tree {
+            self.root = Some(tree.root);
+        }
+        self.focus = Some(update.focus);
+        for (id, node) in &update.nodes {
+            self.nodes.insert(*id, node.clone());
+        }
+
+        // Drops the nodes that were removed from the tree.
+        let reachable: Vec<NodeId> = self.walk().into_iter().map(|(id, _)| id).collect();
+        let mut nodes = std::mem::take(&mut self.nodes);
+        self.nodes = reachable
+            .into_iter()
+            .filter_map(|id| nodes.remove_entry(&id))
+            .collect();
+    }
+
+    /// Returns the node with the keyboard focus.
+    pub(crate) fn focus(&self) -> Option<NodeId> {
+        self.focus
+    }
+
+    /// Returns the nodes in depth-first order, starting with the root.
+    pub(crate) fn walk(&self) -> Vec<(NodeId, &Node)> {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<NodeId> = self.root.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.nodes.get(&id) {
+                nodes.push((id, node));
+                stack.extend(node.children().iter().rev());
+            }
+        }
+        nodes
+    }
+
+    /// Returns the visible nodes with the given label in depth-first order. Widgets that are
+    /// labelled by a label come before the label itself.
+    pub(crate) fn find_label(&self, label: &str) -> Vec<(NodeId, &Node)> {
+        let mut nodes: Vec<(NodeId, &Node)> = self
+            .walk()
+            .into_iter()
+            .filter(|(_, node)| !node.is_hidden() && self.label(node).as_deref() == Some(label))
+            .collect();
+        nodes.sort_by_key(|(_, node)| node.role() == Role::Label);
+        nodes
+    }
+
+    /// Returns the label of the node. Egui stores the text of labels as their value and
+    /// refers to the label of text edits with `labelled_by`.
+    pub(crate) fn label(&self, node: &Node) -> Option<String> {
+        if let Some(label) = node.label() {
+            return Some(label.to_owned());
+        }
+        if !node.labelled_by().is_empty() {
+            let labels: Vec<String> = node
+                .labelled_by()
+                .iter()
+                .filter_map(|id| self.nodes.get(id))
+                .filter_map(|label| label.label().or_else(|| label.value()))
+                .map(str::to_owned)
+                .collect();
+            return Some(labels.join(" "));
+        }
+        match node.role() {
+            Role::Label => node.value().map(str::to_owned),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the rectangle of the node in points. Egui sets the scale factor as transform of the
+/// root, so the bounds of all other nodes are in points.
+pub(crate) fn bounds(node: &Node) -> Option<egui::Rect> {
+    node.bounds().map(|rect| {
+        egui::Rect::from_min_max(
+            egui::pos2(rect.x0 as f32, rect.y0 as f32),
+            egui::pos2(rect.x1 as f32, rect.y1 as f32),
+        )
+    })
+}
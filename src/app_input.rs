@@ -0,0 +1,142 @@
+//! Input state of the application after egui has taken its share.
+
+use std::collections::HashSet;
+
+use egui::Vec2;
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key, PhysicalKey};
+
+use crate::{InputOwner, KeyInput};
+
+/// The keyboard and mouse state that is visible to the application. Input that was
+/// consumed by egui is not included.
+///
+/// Keys and buttons that were pressed while they belonged to the application stay
+/// pressed until they are released, even if egui wants the input in the meantime.
+#[derive(Clone, Debug, Default)]
+pub struct AppInputState {
+    physical_keys_down: HashSet<PhysicalKey>,
+    mouse_buttons_down: HashSet<MouseButton>,
+    pointer_pos: Option<PhysicalPosition<f64>>,
+    scroll_lines: Vec2,
+    scroll_pixels: Vec2,
+
+    // Scrolling for the next frame, which is moved over by `begin_pass`.
+    pending_scroll_lines: Vec2,
+    pending_scroll_pixels: Vec2,
+
+    // The logical key of each held physical key at the time it was pressed. The
+    // logical key of the release can differ, e.g. when shift is released first.
+    logical_keys: Vec<(PhysicalKey, Key)>,
+}
+
+impl AppInputState {
+    /// Returns the physical keys that are currently held down.
+    pub fn physical_keys_down(&self) -> &HashSet<PhysicalKey> {
+        &self.physical_keys_down
+    }
+
+    /// Returns the mouse buttons that are currently held down.
+    pub fn mouse_buttons_down(&self) -> &HashSet<MouseButton> {
+        &self.mouse_buttons_down
+    }
+
+    /// Returns the position of the pointer in physical pixel. `None` if the pointer is outside
+    /// of the window or its movement belongs to egui.
+    pub fn pointer_pos(&self) -> Option<PhysicalPosition<f64>> {
+        self.pointer_pos
+    }
+
+    /// Returns the scrolling in lines that was received for the current frame.
+    pub fn scroll_lines(&self) -> Vec2 {
+        self.scroll_lines
+    }
+
+    /// Returns the scrolling in physical pixel that was received for the current frame.
+    pub fn scroll_pixels(&self) -> Vec2 {
+        self.scroll_pixels
+    }
+
+    /// Returns `true` if the given physical key is held down.
+    pub fn is_physical_key_down(&self, key: PhysicalKey) -> bool {
+        self.physical_keys_down.contains(&key)
+    }
+
+    /// Returns `true` if the given logical key is held down.
+    pub fn is_logical_key_down(&self, key: &Key) -> bool {
+        self.logical_keys.iter().any(|(_, logical)| logical == key)
+    }
+
+    /// Returns the logical keys that are currently held down.
+    pub fn logical_keys_down(&self) -> impl Iterator<Item = &Key> {
+        self.logical_keys.iter().map(|(_, logical)| logical)
+    }
+
+    /// Returns `true` if the given mouse button is held down.
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons_down.contains(&button)
+    }
+
+    /// Updates the state with the given key press or release, which belongs to `owner`.
+    pub(crate) fn update_key(&mut self, input: &KeyInput, owner: Option<InputOwner>) {
+        match input.state {
+            ElementState::Pressed if owner == Some(InputOwner::App) => {
+                if self.physical_keys_down.insert(input.physical_key) {
+                    self.logical_keys
+                        .push((input.physical_key, input.logical_key.clone()));
+                }
+            }
+            ElementState::Pressed => {}
+            ElementState::Released => {
+                self.physical_keys_down.remove(&input.physical_key);
+                self.logical_keys
+                    .retain(|(physical, _)| *physical != input.physical_key);
+            }
+        }
+    }
+
+    /// Updates the state with the given event, which belongs to `owner`.
+    pub(crate) fn update(&mut self, event: &WindowEvent, owner: Option<InputOwner>) {
+        let app = owner == Some(InputOwner::App);
+
+        match event {
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed if app => {
+                    self.mouse_buttons_down.insert(*button);
+                }
+                ElementState::Pressed => {}
+                ElementState::Released => {
+                    self.mouse_buttons_down.remove(button);
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_pos = Some(*position).filter(|_| app);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.pointer_pos = None;
+            }
+            WindowEvent::MouseWheel { delta, .. } if app => match delta {
+                MouseScrollDelta::LineDelta(x, y) => {
+                    self.pending_scroll_lines += Vec2::new(*x, *y);
+                }
+                MouseScrollDelta::PixelDelta(delta) => {
+                    self.pending_scroll_pixels += Vec2::new(delta.x as f32, delta.y as f32);
+                }
+            },
+            // Releases aren't send to unfocused windows.
+            WindowEvent::Focused(false) => {
+                self.physical_keys_down.clear();
+                self.logical_keys.clear();
+                self.mouse_buttons_down.clear();
+            }
+            _ => {}
+        }
+    }
+
+    /// Starts a new frame, which receives the scrolling since the last frame.
+    pub(crate) fn begin_pass(&mut self) {
+        self.scroll_lines = std::mem::take(&mut self.pending_scroll_lines);
+        self.scroll_pixels = std::mem::take(&mut self.pending_scroll_pixels);
+    }
+}
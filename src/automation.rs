@@ -0,0 +1,654 @@
+//! A local server that lets scripts drive the UI through a JSON protocol.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use egui::accesskit::Toggled;
+use egui::{ColorImage, Context, Event, Pos2};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+use winit::keyboard::{Key, ModifiersState};
+
+use crate::access_tree::{self, AccessTree};
+use crate::keyboard::key_presses;
+use crate::Platform;
+
+/// Where the automation server listens. See [`crate::Platform::start_automation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AutomationAddress {
+    /// A TCP socket. Only loopback addresses are allowed, use port 0 to pick a free port.
+    Tcp(SocketAddr),
+    /// A Unix domain socket at the given path. A socket file left behind by a server that no
+    /// longer runs is replaced, and the file is removed when the server stops.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+/// A request received by a connection, which is answered on the UI thread.
+struct Request {
+    message: Value,
+    reply: Sender<Value>,
+}
+
+/// The UI thread side of the server. Dropping it stops the server.
+pub(crate) struct AutomationServer {
+    requests: Receiver<Request>,
+    address: AutomationAddress,
+    connections: Arc<Mutex<Connections>>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+/// The open connections, which are shut down when the server stops.
+#[derive(Default)]
+struct Connections {
+    stopped: bool,
+    next_id: usize,
+    open: HashMap<usize, Box<dyn Fn() + Send>>,
+}
+
+/// A connected socket.
+trait Stream: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+    fn shutdown(&self);
+}
+
+impl Stream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn shutdown(&self) {
+        let _ = TcpStream::shutdown(self, Shutdown::Both);
+    }
+}
+
+#[cfg(unix)]
+impl Stream for UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        UnixStream::try_clone(self)
+    }
+
+    fn shutdown(&self) {
+        let _ = UnixStream::shutdown(self, Shutdown::Both);
+    }
+}
+
+impl AutomationServer {
+    /// Binds the socket and accepts connections on a background thread. Returns the bound
+    /// address, which differs from `address` for TCP port 0.
+    pub(crate) fn start(
+        address: &AutomationAddress,
+        context: Context,
+    ) -> io::Result<(Self, AutomationAddress)> {
+        let (sender, requests) = mpsc::channel();
+        let connections = Arc::new(Mutex::new(Connections::default()));
+        let accepted = connections.clone();
+        let (bound, accept_thread) = match address {
+            AutomationAddress::Tcp(addr) => {
+                if !addr.ip().is_loopback() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "The automation server only listens on loopback addresses",
+                    ));
+                }
+                let listener = TcpListener::bind(addr)?;
+                let bound = AutomationAddress::Tcp(listener.local_addr()?);
+                let thread = std::thread::spawn(move || {
+                    accept(listener.incoming(), sender, context, accepted)
+                });
+                (bound, thread)
+            }
+            #[cfg(unix)]
+            AutomationAddress::Unix(path) => {
+                remove_stale_socket(path)?;
+                let listener = UnixListener::bind(path)?;
+                let thread = std::thread::spawn(move || {
+                    accept(listener.incoming(), sender, context, accepted)
+                });
+                (address.clone(), thread)
+            }
+        };
+
+        let server = Self {
+            requests,
+            address: bound.clone(),
+            connections,
+            accept_thread: Some(accept_thread),
+        };
+        Ok((server, bound))
+    }
+}
+
+impl Drop for AutomationServer {
+    fn drop(&mut self) {
+        {
+            let mut connections = lock(&self.connections);
+            connections.stopped = true;
+            for (_, shutdown) in connections.open.drain() {
+                shutdown();
+            }
+        }
+
+        // Wakes the accept thread with a connection, so that it sees that the server stopped.
+        let woken = match &self.address {
+            AutomationAddress::Tcp(addr) => TcpStream::connect(addr).is_ok(),
+            #[cfg(unix)]
+            AutomationAddress::Unix(path) => UnixStream::connect(path).is_ok(),
+        };
+        if let Some(thread) = self.accept_thread.take() {
+            if woken {
+                let _ = thread.join();
+            }
+        }
+
+        #[cfg(unix)]
+        if let AutomationAddress::Unix(path) = &self.address {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Removes the socket file at `path` if no server listens on it anymore.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and isn't a socket", path.display()),
+        ));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("Another server listens on {}", path.display()),
+        ));
+    }
+    std::fs::remove_file(path)
+}
+
+fn lock(connections: &Mutex<Connections>) -> MutexGuard<'_, Connections> {
+    connections
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Spawns a thread for each connection until the server stops.
+fn accept<S: Stream>(
+    incoming: impl Iterator<Item = io::Result<S>>,
+    requests: Sender<Request>,
+    context: Context,
+    connections: Arc<Mutex<Connections>>,
+) {
+    for stream in incoming {
+        let mut open = lock(&connections);
+        if open.stopped {
+            return;
+        }
+        let accepted = stream.and_then(|stream| {
+            let reader = stream.try_clone()?;
+            let closer = stream.try_clone()?;
+            let id = open.next_id;
+            open.next_id += 1;
+            open.open.insert(id, Box::new(move || closer.shutdown()));
+            let connections = connections.clone();
+            let (requests, context) = (requests.clone(), context.clone());
+            std::thread::spawn(move || {
+                serve(reader, stream, &requests, &context);
+                lock(&connections).open.remove(&id);
+            });
+            Ok(())
+        });
+        if let Err(err) = accepted {
+            eprintln!("Can't accept automation connection: {}", err);
+        }
+    }
+}
+
+/// Requests longer than this many bytes are refused, so that clients can't make the server
+/// buffer unlimited input.
+const MAX_REQUEST_LENGTH: u64 = 1 << 20;
+
+/// The number of frames a `frames` request runs at most.
+const MAX_FRAMES: usize = 100;
+
+/// Serves the requests of a connection, one JSON object per line.
+fn serve(reader: impl Read, mut writer: impl Write, requests: &Sender<Request>, context: &Context) {
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match (&mut reader)
+            .take(MAX_REQUEST_LENGTH + 1)
+            .read_until(b'\n', &mut line)
+        {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let response = if line.last() != Some(&b'\n') && line.len() as u64 > MAX_REQUEST_LENGTH {
+            // Skips the rest of the request without buffering it.
+            if reader.skip_until(b'\n').is_err() {
+                break;
+            }
+            error_response(None, "The request is too long".to_owned())
+        } else {
+            match serde_json::from_slice(&line) {
+                Ok(message) => {
+                    let (reply, response) = mpsc::channel();
+                    if requests.send(Request { message, reply }).is_err() {
+                        break;
+                    }
+                    // Wakes the event loop, see `Platform::set_repaint_proxy`.
+                    context.request_repaint();
+                    match response.recv() {
+                        Ok(response) => response,
+                        Err(_) => break,
+                    }
+                }
+                Err(err) => error_response(None, format!("Invalid JSON: {}", err)),
+            }
+        };
+        if writeln!(writer, "{}", response)
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Answers the requests that arrived since the last call. Called by
+/// [`crate::Platform::process_automation`].
+pub(crate) fn process(
+    platform: &mut Platform,
+    frame: &mut dyn FnMut(&mut Platform) -> Option<ColorImage>,
+) {
+    let requests: Vec<Request> = match &platform.automation {
+        Some(server) => server.requests.try_iter().collect(),
+        None => return,
+    };
+    for request in requests {
+        let id = request.message.get("id").cloned();
+        let result = Command::deserialize(&request.message)
+            .map_err(|err| err.to_string())
+            .and_then(|command| handle(platform, command, frame));
+        let response = match result {
+            Ok(mut members) => {
+                members.insert("ok".to_owned(), Value::Bool(true));
+                if let Some(id) = id {
+                    members.insert("id".to_owned(), id);
+                }
+                Value::Object(members)
+            }
+            Err(err) => error_response(id, err),
+        };
+        // The connection may have been closed in the meantime.
+        let _ = request.reply.send(response);
+    }
+}
+
+fn error_response(id: Option<Value>, error: String) -> Value {
+    let mut members = Map::new();
+    if let Some(id) = id {
+        members.insert("id".to_owned(), id);
+    }
+    members.insert("ok".to_owned(), Value::Bool(false));
+    members.insert("error".to_owned(), Value::String(error));
+    Value::Object(members)
+}
+
+/// A request of the protocol, see [`crate::Platform::start_automation`].
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    PointerMove {
+        x: f32,
+        y: f32,
+    },
+    PointerButton {
+        x: f32,
+        y: f32,
+        pressed: bool,
+        #[serde(default)]
+        button: Button,
+        #[serde(default)]
+        modifiers: Vec<Modifier>,
+    },
+    Click {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        button: Button,
+        #[serde(default)]
+        modifiers: Vec<Modifier>,
+    },
+    ClickLabel {
+        label: String,
+        #[serde(default)]
+        button: Button,
+        #[serde(default)]
+        modifiers: Vec<Modifier>,
+    },
+    Key {
+        key: String,
+        pressed: Option<bool>,
+        #[serde(default)]
+        modifiers: Vec<Modifier>,
+    },
+    Text {
+        text: String,
+    },
+    Frames {
+        #[serde(default = "one")]
+        count: usize,
+    },
+    Tree,
+    Screenshot,
+}
+
+fn one() -> usize {
+    1
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Button {
+    #[default]
+    Primary,
+    Secondary,
+    Middle,
+}
+
+impl From<Button> for MouseButton {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::Primary => MouseButton::Left,
+            Button::Secondary => MouseButton::Right,
+            Button::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Modifier {
+    Alt,
+    Ctrl,
+    Shift,
+    MacCmd,
+    Command,
+}
+
+fn modifier_state(names: &[Modifier]) -> ModifiersState {
+    let mut state = ModifiersState::empty();
+    for name in names {
+        state |= match name {
+            Modifier::Alt => ModifiersState::ALT,
+            Modifier::Ctrl => ModifiersState::CONTROL,
+            Modifier::Shift => ModifiersState::SHIFT,
+            Modifier::MacCmd => ModifiersState::SUPER,
+            Modifier::Command if cfg!(target_os = "macos") => ModifiersState::SUPER,
+            Modifier::Command => ModifiersState::CONTROL,
+        };
+    }
+    state
+}
+
+/// Holds the modifiers in addition to the held ones while sending the input.
+fn with_modifiers(platform: &mut Platform, names: &[Modifier], input: impl FnOnce(&mut Platform)) {
+    let held = platform.modifier_state;
+    let modifiers = held | modifier_state(names);
+    if modifiers != held {
+        platform.handle_event(&WindowEvent::ModifiersChanged(modifiers.into()));
+    }
+    input(platform);
+    if modifiers != held {
+        platform.handle_event(&WindowEvent::ModifiersChanged(held.into()));
+    }
+}
+
+/// Moves the pointer to `pos` in points and presses or releases the button, like the mouse.
+fn mouse_input(platform: &mut Platform, pos: Pos2, button: Button, presses: &[bool]) {
+    let scale_factor = platform.scale_factor;
+    platform.handle_event(&WindowEvent::CursorMoved {
+        device_id: DeviceId::dummy(),
+        position: PhysicalPosition::new(pos.x as f64 * scale_factor, pos.y as f64 * scale_factor),
+    });
+    let button = MouseButton::from(button);
+    for pressed in presses {
+        platform.handle_event(&WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state: if *pressed {
+                ElementState::Pressed
+            } else {
+                ElementState::Released
+            },
+            button,
+        });
+    }
+}
+
+/// Sends the input like the winit events of a user would, so that it's handled like them by
+/// input capture, the app input, the keymap and the modifiers.
+fn handle(
+    platform: &mut Platform,
+    command: Command,
+    frame: &mut dyn FnMut(&mut Platform) -> Option<ColorImage>,
+) -> Result<Map<String, Value>, String> {
+    match command {
+        Command::PointerMove { x, y } => {
+            mouse_input(platform, egui::pos2(x, y), Button::Primary, &[])
+        }
+        Command::PointerButton {
+            x,
+            y,
+            pressed,
+            button,
+            modifiers,
+        } => with_modifiers(platform, &modifiers, |platform| {
+            mouse_input(platform, egui::pos2(x, y), button, &[pressed])
+        }),
+        Command::Click {
+            x,
+            y,
+            button,
+            modifiers,
+        } => with_modifiers(platform, &modifiers, |platform| {
+            mouse_input(platform, egui::pos2(x, y), button, &[true, false])
+        }),
+        Command::ClickLabel {
+            label,
+            button,
+            modifiers,
+        } => {
+            let rect = platform
+                .access_tree
+                .find_label(&label)
+                .into_iter()
+                .find_map(|(_, node)| access_tree::bounds(node))
+                .ok_or(format!("No widget with the label \"{}\"", label))?;
+            with_modifiers(platform, &modifiers, |platform| {
+                mouse_input(platform, rect.center(), button, &[true, false])
+            });
+        }
+        Command::Key {
+            key,
+            pressed,
+            modifiers,
+        } => {
+            let key = egui::Key::from_name(&key).ok_or(format!("Unknown key \"{}\"", key))?;
+            let [press, release] = key_presses(crate::egui_to_winit_key(key));
+            let inputs = match pressed {
+                Some(true) => vec![press],
+                Some(false) => vec![release],
+                None => vec![press, release],
+            };
+            with_modifiers(platform, &modifiers, |platform| {
+                for input in &inputs {
+                    platform.handle_key_input(input);
+                }
+            });
+        }
+        Command::Text { text } => {
+            let mut buffer = [0; 4];
+            for c in text.chars() {
+                let key = Key::Character(c.encode_utf8(&mut buffer).into());
+                for input in &key_presses(key) {
+                    platform.handle_key_input(input);
+                }
+            }
+        }
+        Command::Frames { count } => {
+            let count = count.min(MAX_FRAMES);
+            for _ in 0..count {
+                frame(platform);
+            }
+            let mut members = Map::new();
+            members.insert("frames".to_owned(), count.into());
+            return Ok(members);
+        }
+        Command::Tree => {
+            let mut members = Map::new();
+            members.insert("nodes".to_owned(), tree_json(&platform.access_tree));
+            return Ok(members);
+        }
+        Command::Screenshot => {
+            platform
+                .screenshot_requests
+                .push(egui::UserData::new(AutomationScreenshot));
+            let image = frame(platform);
+            // The application doesn't expect screenshots it didn't request.
+            platform.raw_input.events.retain(|event| {
+                !matches!(event, Event::Screenshot { user_data, .. }
+                    if user_data.data.as_ref().is_some_and(|data| data.is::<AutomationScreenshot>()))
+            });
+            let image = image.ok_or("The renderer doesn't provide screenshots")?;
+            let rgba: Vec<u8> = image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_array())
+                .collect();
+            let mut members = Map::new();
+            members.insert("width".to_owned(), image.size[0].into());
+            members.insert("height".to_owned(), image.size[1].into());
+            members.insert("rgba".to_owned(), BASE64_STANDARD.encode(rgba).into());
+            return Ok(members);
+        }
+    }
+    Ok(Map::new())
+}
+
+/// The user data of the screenshots requested by the automation server.
+struct AutomationScreenshot;
+
+/// A node of the `tree` response.
+#[derive(Serialize)]
+struct TreeNode {
+    // The IDs are hashes, which don't fit into the numbers of JSON.
+    id: String,
+    role: String,
+    label: Option<String>,
+    value: Option<String>,
+    numeric_value: Option<f64>,
+    rect: Option<[f64; 4]>,
+    toggled: Option<&'static str>,
+    disabled: bool,
+    hidden: bool,
+    focused: bool,
+    children: Vec<String>,
+}
+
+fn tree_json(tree: &AccessTree) -> Value {
+    let nodes: Vec<TreeNode> = tree
+        .walk()
+        .into_iter()
+        .map(|(id, node)| TreeNode {
+            id: id.0.to_string(),
+            role: format!("{:?}", node.role()),
+            label: tree.label(node),
+            value: node.value().map(str::to_owned),
+            numeric_value: node.numeric_value(),
+            rect: node
+                .bounds()
+                .map(|rect| [rect.x0, rect.y0, rect.x1, rect.y1]),
+            toggled: node.toggled().map(|toggled| match toggled {
+                Toggled::True => "true",
+                Toggled::False => "false",
+                Toggled::Mixed => "mixed",
+            }),
+            disabled: node.is_disabled(),
+            hidden: node.is_hidden(),
+            focused: tree.focus() == Some(id),
+            children: node.children().iter().map(|id| id.0.to_string()).collect(),
+        })
+        .collect();
+    serde_json::to_value(nodes).unwrap_or_default()
+}
+
+/// Talks to an automation server, e.g. from a test.
+pub struct AutomationClient {
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+impl AutomationClient {
+    /// Connects to the server at the given address.
+    pub fn connect(address: &AutomationAddress) -> io::Result<Self> {
+        match address {
+            AutomationAddress::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                Ok(Self {
+                    reader: Box::new(BufReader::new(stream.try_clone()?)),
+                    writer: Box::new(stream),
+                })
+            }
+            #[cfg(unix)]
+            AutomationAddress::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                Ok(Self {
+                    reader: Box::new(BufReader::new(stream.try_clone()?)),
+                    writer: Box::new(stream),
+                })
+            }
+        }
+    }
+
+    /// Sends a request and waits for the response. The request is answered when the
+    /// application calls [`crate::Platform::process_automation`].
+    pub fn request(&mut self, request: &Value) -> io::Result<Value> {
+        let response = self.request_line(&request.to_string())?;
+        serde_json::from_str(&response).map_err(io::Error::from)
+    }
+
+    /// Sends a request that is already encoded as JSON and returns the JSON of the response.
+    /// The request must not contain line breaks.
+    pub fn request_line(&mut self, request: &str) -> io::Result<String> {
+        writeln!(self.writer, "{}", request)?;
+        self.writer.flush()?;
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(line)
+    }
+}
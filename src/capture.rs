@@ -0,0 +1,166 @@
+//! Tracks which side owns the input across presses, drags and releases.
+
+use std::collections::HashMap;
+
+use egui::{Context, Pos2};
+use winit::event::{ElementState, MouseButton, TouchPhase, WindowEvent};
+use winit::keyboard::PhysicalKey;
+
+use crate::gestures::Finger;
+
+/// The receiver of an input event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InputOwner {
+    /// The event belongs to egui and should be ignored by the application.
+    Egui,
+    /// The event belongs to the application.
+    App,
+}
+
+/// Describes how an event was handled by [`crate::Platform::handle_event`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventResponse {
+    /// The receiver of the event. `None` for events that aren't pointer, touch or
+    /// keyboard input, which concern both egui and the application.
+    pub owner: Option<InputOwner>,
+    /// Whether egui received input from the event and a frame should be drawn to show its
+    /// effect.
+    pub repaint: bool,
+}
+
+impl EventResponse {
+    /// Returns `true` if the event belongs to egui and should be ignored by the application.
+    pub fn captured_by_egui(&self) -> bool {
+        self.owner == Some(InputOwner::Egui)
+    }
+}
+
+/// Input ownership that was decided when a button, finger or key was pressed. It
+/// stays until it is released again.
+#[derive(Default)]
+pub(crate) struct InputCapture {
+    mouse: Option<InputOwner>,
+    mouse_buttons: Vec<MouseButton>,
+    touches: HashMap<Finger, InputOwner>,
+    keys: HashMap<PhysicalKey, InputOwner>,
+}
+
+impl InputCapture {
+    /// Returns the owner of the given event. `finger` must be set for touch events of known
+    /// devices.
+    pub(crate) fn owner(
+        &self,
+        context: &Context,
+        event: &WindowEvent,
+        finger: Option<Finger>,
+        scale_factor: f64,
+    ) -> Option<InputOwner> {
+        let egui_if = |egui: bool| {
+            if egui {
+                InputOwner::Egui
+            } else {
+                InputOwner::App
+            }
+        };
+
+        match event {
+            WindowEvent::KeyboardInput { event, .. } => {
+                Some(self.key_owner(context, event.physical_key))
+            }
+            WindowEvent::ModifiersChanged(_) => Some(egui_if(context.wants_keyboard_input())),
+            WindowEvent::MouseInput { .. } | WindowEvent::MouseWheel { .. } => Some(
+                self.mouse
+                    .unwrap_or_else(|| egui_if(context.wants_pointer_input())),
+            ),
+            WindowEvent::CursorMoved { .. } => Some(
+                self.mouse
+                    .unwrap_or_else(|| egui_if(context.is_using_pointer())),
+            ),
+            WindowEvent::Touch(touch) => Some(
+                finger
+                    .and_then(|finger| self.touches.get(&finger).copied())
+                    .unwrap_or_else(|| {
+                        let pos = Pos2::new(
+                            (touch.location.x / scale_factor) as f32,
+                            (touch.location.y / scale_factor) as f32,
+                        );
+                        egui_if(context.is_using_pointer() || is_pos_over_area(context, pos))
+                    }),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the owner of a press or release of the given key.
+    pub(crate) fn key_owner(&self, context: &Context, key: PhysicalKey) -> InputOwner {
+        match self.keys.get(&key) {
+            Some(owner) => *owner,
+            None if context.wants_keyboard_input() => InputOwner::Egui,
+            None => InputOwner::App,
+        }
+    }
+
+    /// Updates the captures with a press or release of the given key, which belongs to `owner`.
+    pub(crate) fn update_key(&mut self, key: PhysicalKey, state: ElementState, owner: InputOwner) {
+        match state {
+            ElementState::Pressed => {
+                self.keys.insert(key, owner);
+            }
+            ElementState::Released => {
+                self.keys.remove(&key);
+            }
+        }
+    }
+
+    /// Updates the captures with the given event, which belongs to `owner`.
+    pub(crate) fn update(
+        &mut self,
+        event: &WindowEvent,
+        finger: Option<Finger>,
+        owner: Option<InputOwner>,
+    ) {
+        match (event, owner) {
+            (WindowEvent::MouseInput { state, button, .. }, Some(owner)) => match state {
+                ElementState::Pressed => {
+                    if !self.mouse_buttons.contains(button) {
+                        self.mouse_buttons.push(*button);
+                    }
+                    self.mouse = Some(owner);
+                }
+                ElementState::Released => {
+                    self.mouse_buttons.retain(|b| b != button);
+                    if self.mouse_buttons.is_empty() {
+                        self.mouse = None;
+                    }
+                }
+            },
+            (WindowEvent::Touch(touch), Some(owner)) => {
+                if let Some(finger) = finger {
+                    match touch.phase {
+                        TouchPhase::Started | TouchPhase::Moved => {
+                            self.touches.insert(finger, owner);
+                        }
+                        TouchPhase::Ended | TouchPhase::Cancelled => {
+                            self.touches.remove(&finger);
+                        }
+                    }
+                }
+            }
+            // Releases aren't send to unfocused windows.
+            (WindowEvent::Focused(false), _) => {
+                *self = Self::default();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Is the given position over any egui area? The pointer position egui knows of is
+/// outdated for new touches.
+fn is_pos_over_area(context: &Context, pos: Pos2) -> bool {
+    match context.layer_id_at(pos) {
+        Some(layer) if layer.order == egui::Order::Background => context.used_rect().contains(pos),
+        Some(_) => true,
+        None => false,
+    }
+}
@@ -0,0 +1,379 @@
+//! Tracks the cursor requested by egui and decides what has to be applied to the window.
+
+use std::collections::HashMap;
+
+use egui::viewport::CursorGrab;
+use winit::window::{Cursor, CursorIcon, CustomCursor};
+
+/// A cursor of the window, either a winit cursor icon or a custom cursor of type `C`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum WindowCursor<C> {
+    Icon(CursorIcon),
+    Custom(C),
+}
+
+impl From<WindowCursor<CustomCursor>> for Cursor {
+    fn from(cursor: WindowCursor<CustomCursor>) -> Self {
+        match cursor {
+            WindowCursor::Icon(icon) => Cursor::Icon(icon),
+            WindowCursor::Custom(cursor) => Cursor::Custom(cursor),
+        }
+    }
+}
+
+/// The changes to apply to the window. `None` fields are left as they are.
+#[derive(Debug, PartialEq)]
+pub(crate) struct CursorUpdate<C> {
+    pub(crate) grab: Option<CursorGrab>,
+    pub(crate) visible: Option<bool>,
+    pub(crate) cursor: Option<WindowCursor<C>>,
+}
+
+impl<C> CursorUpdate<C> {
+    /// Returns `true` if nothing has to be applied.
+    #[cfg(test)]
+    fn is_empty(&self) -> bool {
+        self.grab.is_none() && self.visible.is_none() && self.cursor.is_none()
+    }
+}
+
+/// The cursor state requested by egui through viewport commands and cursor icons, and the
+/// state that was last applied to the window. Only changes are applied, so that the window
+/// isn't called every frame.
+pub(crate) struct CursorState<C> {
+    grab: CursorGrab,
+    visible: bool,
+    reapply_grab: bool,
+
+    // Custom cursors registered by the application and the egui cursor icons
+    // they replace.
+    custom_cursors: HashMap<String, C>,
+    overrides: Vec<(egui::CursorIcon, String)>,
+
+    applied_visible: Option<bool>,
+    applied_cursor: Option<WindowCursor<C>>,
+}
+
+impl<C> Default for CursorState<C> {
+    fn default() -> Self {
+        Self {
+            grab: CursorGrab::None,
+            visible: true,
+            reapply_grab: false,
+            custom_cursors: HashMap::new(),
+            overrides: Vec::new(),
+            applied_visible: None,
+            applied_cursor: None,
+        }
+    }
+}
+
+impl<C: Clone + PartialEq> CursorState<C> {
+    /// Handles the cursor related viewport commands of egui.
+    pub(crate) fn command(&mut self, command: &egui::ViewportCommand) {
+        match command {
+            egui::ViewportCommand::CursorGrab(grab) => {
+                self.grab = *grab;
+                self.reapply_grab = true;
+            }
+            egui::ViewportCommand::CursorVisible(visible) => {
+                self.visible = *visible;
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the changes that show the cursor `icon` of egui. The cursor itself is only
+    /// set while the pointer is inside the window.
+    pub(crate) fn update(
+        &mut self,
+        icon: egui::CursorIcon,
+        pointer_in_window: bool,
+    ) -> CursorUpdate<C> {
+        let grab = if self.reapply_grab {
+            self.reapply_grab = false;
+            Some(self.grab)
+        } else {
+            None
+        };
+
+        let cursor = self.window_cursor(icon).filter(|_| self.visible);
+        let visible = cursor.is_some();
+        let visible = if self.applied_visible != Some(visible) {
+            self.applied_visible = Some(visible);
+            Some(visible)
+        } else {
+            None
+        };
+
+        let cursor = match cursor.filter(|_| pointer_in_window) {
+            Some(cursor) if self.applied_cursor.as_ref() != Some(&cursor) => {
+                self.applied_cursor = Some(cursor.clone());
+                Some(cursor)
+            }
+            _ => None,
+        };
+
+        CursorUpdate {
+            grab,
+            visible,
+            cursor,
+        }
+    }
+
+    /// Makes the next [`Self::update`] apply everything again, e.g. because the window lost
+    /// it while it wasn't focused.
+    pub(crate) fn reapply(&mut self) {
+        self.applied_visible = None;
+        self.applied_cursor = None;
+        self.reapply_grab = self.grab != CursorGrab::None;
+    }
+
+    /// Returns the cursor grab mode that was last requested by egui.
+    pub(crate) fn grab(&self) -> CursorGrab {
+        self.grab
+    }
+
+    pub(crate) fn register_custom_cursor(&mut self, id: String, cursor: C) {
+        self.custom_cursors.insert(id, cursor);
+    }
+
+    pub(crate) fn remove_custom_cursor(&mut self, id: &str) {
+        self.custom_cursors.remove(id);
+    }
+
+    pub(crate) fn set_override(&mut self, icon: egui::CursorIcon, id: Option<&str>) {
+        self.overrides.retain(|(overridden, _)| *overridden != icon);
+        if let Some(id) = id {
+            self.overrides.push((icon, id.to_owned()));
+        }
+    }
+
+    /// Returns the window cursor for the given egui cursor icon, taking the custom cursor
+    /// overrides into account.
+    fn window_cursor(&self, icon: egui::CursorIcon) -> Option<WindowCursor<C>> {
+        let custom_cursor = self
+            .overrides
+            .iter()
+            .find(|(overridden, _)| *overridden == icon)
+            .and_then(|(_, id)| self.custom_cursors.get(id));
+
+        match custom_cursor {
+            Some(cursor) => Some(WindowCursor::Custom(cursor.clone())),
+            None => crate::egui_to_winit_cursor_icon(icon).map(WindowCursor::Icon),
+        }
+    }
+}
+
+/// Applies the changes to the window.
+pub(crate) fn apply(window: &winit::window::Window, update: CursorUpdate<CustomCursor>) {
+    if let Some(grab) = update.grab {
+        set_cursor_grab(window, grab);
+    }
+    if let Some(visible) = update.visible {
+        window.set_cursor_visible(visible);
+    }
+    if let Some(cursor) = update.cursor {
+        window.set_cursor(Cursor::from(cursor));
+    }
+}
+
+/// Grabs the cursor. Falls back to the other grab mode if the platform doesn't support the
+/// requested one.
+fn set_cursor_grab(window: &winit::window::Window, grab: CursorGrab) {
+    use winit::window::CursorGrabMode;
+
+    let (mode, fallback) = match grab {
+        CursorGrab::None => (CursorGrabMode::None, None),
+        CursorGrab::Confined => (CursorGrabMode::Confined, Some(CursorGrabMode::Locked)),
+        CursorGrab::Locked => (CursorGrabMode::Locked, Some(CursorGrabMode::Confined)),
+    };
+
+    if let Err(err) = window
+        .set_cursor_grab(mode)
+        .or_else(|err| fallback.map_or(Err(err), |mode| window.set_cursor_grab(mode)))
+    {
+        eprintln!("Failed to grab cursor: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Custom cursors are stood in for by their names, since winit can only create them
+    /// with an event loop.
+    type State = CursorState<&'static str>;
+
+    fn icon(icon: CursorIcon) -> Option<WindowCursor<&'static str>> {
+        Some(WindowCursor::Icon(icon))
+    }
+
+    #[test]
+    fn unchanged_cursor_isnt_applied_again() {
+        let mut state = State::default();
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true),
+            CursorUpdate {
+                grab: None,
+                visible: Some(true),
+                cursor: icon(CursorIcon::Text),
+            }
+        );
+        assert!(state.update(egui::CursorIcon::Text, true).is_empty());
+
+        assert_eq!(
+            state.update(egui::CursorIcon::PointingHand, true),
+            CursorUpdate {
+                grab: None,
+                visible: None,
+                cursor: icon(CursorIcon::Pointer),
+            }
+        );
+        assert!(state
+            .update(egui::CursorIcon::PointingHand, true)
+            .is_empty());
+    }
+
+    #[test]
+    fn cursor_is_only_set_inside_the_window() {
+        let mut state = State::default();
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, false),
+            CursorUpdate {
+                grab: None,
+                visible: Some(true),
+                cursor: None,
+            }
+        );
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true).cursor,
+            icon(CursorIcon::Text)
+        );
+    }
+
+    #[test]
+    fn reapply_applies_everything_again() {
+        let mut state = State::default();
+        state.update(egui::CursorIcon::Text, true);
+        state.reapply();
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true),
+            CursorUpdate {
+                grab: None,
+                visible: Some(true),
+                cursor: icon(CursorIcon::Text),
+            }
+        );
+        assert!(state.update(egui::CursorIcon::Text, true).is_empty());
+    }
+
+    #[test]
+    fn grab_is_applied_once_and_after_focus_returns() {
+        let mut state = State::default();
+        state.update(egui::CursorIcon::Default, true);
+
+        state.command(&egui::ViewportCommand::CursorGrab(CursorGrab::Locked));
+        assert_eq!(state.grab(), CursorGrab::Locked);
+        assert_eq!(
+            state.update(egui::CursorIcon::Default, true).grab,
+            Some(CursorGrab::Locked)
+        );
+        assert!(state.update(egui::CursorIcon::Default, true).is_empty());
+
+        state.reapply();
+        assert_eq!(
+            state.update(egui::CursorIcon::Default, true).grab,
+            Some(CursorGrab::Locked)
+        );
+
+        // A released grab doesn't have to be released again.
+        state.command(&egui::ViewportCommand::CursorGrab(CursorGrab::None));
+        assert_eq!(
+            state.update(egui::CursorIcon::Default, true).grab,
+            Some(CursorGrab::None)
+        );
+        state.reapply();
+        assert_eq!(state.update(egui::CursorIcon::Default, true).grab, None);
+    }
+
+    #[test]
+    fn hidden_cursor_isnt_set() {
+        let mut state = State::default();
+        state.command(&egui::ViewportCommand::CursorVisible(false));
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true),
+            CursorUpdate {
+                grab: None,
+                visible: Some(false),
+                cursor: None,
+            }
+        );
+
+        state.command(&egui::ViewportCommand::CursorVisible(true));
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true),
+            CursorUpdate {
+                grab: None,
+                visible: Some(true),
+                cursor: icon(CursorIcon::Text),
+            }
+        );
+
+        // Egui hides the cursor with an icon too.
+        assert_eq!(
+            state.update(egui::CursorIcon::None, true),
+            CursorUpdate {
+                grab: None,
+                visible: Some(false),
+                cursor: None,
+            }
+        );
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true).visible,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn custom_cursor_replaces_the_icon() {
+        let mut state = State::default();
+        state.register_custom_cursor("pen".to_owned(), "pen image");
+        state.set_override(egui::CursorIcon::Crosshair, Some("pen"));
+
+        assert_eq!(
+            state.update(egui::CursorIcon::Crosshair, true).cursor,
+            Some(WindowCursor::Custom("pen image"))
+        );
+        assert_eq!(
+            state.update(egui::CursorIcon::Text, true).cursor,
+            icon(CursorIcon::Text)
+        );
+
+        // A replaced cursor is applied although the id stayed the same.
+        state.update(egui::CursorIcon::Crosshair, true);
+        state.register_custom_cursor("pen".to_owned(), "thick pen image");
+        assert_eq!(
+            state.update(egui::CursorIcon::Crosshair, true).cursor,
+            Some(WindowCursor::Custom("thick pen image"))
+        );
+    }
+
+    #[test]
+    fn removed_custom_cursor_falls_back_to_the_icon() {
+        let mut state = State::default();
+        state.register_custom_cursor("pen".to_owned(), "pen image");
+        state.set_override(egui::CursorIcon::Crosshair, Some("pen"));
+        state.update(egui::CursorIcon::Crosshair, true);
+
+        state.remove_custom_cursor("pen");
+        assert_eq!(
+            state.update(egui::CursorIcon::Crosshair, true).cursor,
+            icon(CursorIcon::Crosshair)
+        );
+
+        state.register_custom_cursor("pen".to_owned(), "pen image");
+        state.set_override(egui::CursorIcon::Crosshair, None);
+        assert!(state.update(egui::CursorIcon::Crosshair, true).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! Finds the regions of the screen that changed since the last frame.
+
+use egui::epaint::{ClippedShape, Shape};
+use egui::{Rect, TextureId, TexturesDelta};
+
+/// More regions than this are merged into one.
+const MAX_REGIONS: usize = 8;
+
+/// Remembers the shapes of the last frame to compare them with the next one.
+#[derive(Default)]
+pub(crate) struct DamageTracker {
+    previous: Vec<ClippedShape>,
+    previous_screen: Option<([u32; 2], f32)>,
+}
+
+impl DamageTracker {
+    /// Returns the rectangles in physical pixel that have to be redrawn. The shapes that are
+    /// equal at the beginning and the end of both frames are skipped, all other shapes damage
+    /// the area they covered in the last frame and cover in this frame. Shapes that use an
+    /// updated texture and paint callbacks are always damaged.
+    pub(crate) fn update(
+        &mut self,
+        shapes: &[ClippedShape],
+        textures_delta: &TexturesDelta,
+        screen_size_in_pixels: [u32; 2],
+        pixels_per_point: f32,
+    ) -> Vec<Rect> {
+        let screen = Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(
+                screen_size_in_pixels[0] as f32,
+                screen_size_in_pixels[1] as f32,
+            ),
+        );
+        let previous = std::mem::replace(&mut self.previous, shapes.to_vec());
+        let screen_changed = self
+            .previous_screen
+            .replace((screen_size_in_pixels, pixels_per_point))
+            != Some((screen_size_in_pixels, pixels_per_point));
+        if screen_changed {
+            return vec![screen];
+        }
+
+        let prefix = previous
+            .iter()
+            .zip(shapes)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let suffix = previous[prefix..]
+            .iter()
+            .rev()
+            .zip(shapes[prefix..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        let updated_textures: Vec<TextureId> =
+            textures_delta.set.iter().map(|(id, _)| *id).collect();
+        let always_damaged = |clipped: &&ClippedShape| {
+            has_callback(&clipped.shape)
+                || updated_textures
+                    .iter()
+                    .any(|id| uses_texture(&clipped.shape, *id))
+        };
+
+        let damaged = previous[prefix..previous.len() - suffix]
+            .iter()
+            .chain(&shapes[prefix..shapes.len() - suffix])
+            .chain(shapes[..prefix].iter().filter(always_damaged))
+            .chain(
+                shapes[shapes.len() - suffix..]
+                    .iter()
+                    .filter(always_damaged),
+            );
+
+        let mut regions: Vec<Rect> = Vec::new();
+        for clipped in damaged {
+            let rect = clipped
+                .shape
+                .visual_bounding_rect()
+                .intersect(clipped.clip_rect);
+            if !rect.is_positive() {
+                continue;
+            }
+            // Grown by a pixel for the anti-aliasing.
+            let rect = Rect::from_min_max(
+                (rect.min.to_vec2() * pixels_per_point).floor().to_pos2(),
+                (rect.max.to_vec2() * pixels_per_point).ceil().to_pos2(),
+            )
+            .expand(1.0)
+            .intersect(screen);
+            if rect.is_positive() {
+                add_region(&mut regions, rect);
+            }
+        }
+
+        if regions.len() > MAX_REGIONS {
+            let bounds = regions.iter().fold(Rect::NOTHING, |a, b| a.union(*b));
+            regions = vec![bounds];
+        }
+        regions
+    }
+}
+
+/// Adds the rectangle to the regions and merges the regions that overlap it.
+fn add_region(regions: &mut Vec<Rect>, mut rect: Rect) {
+    while let Some(index) = regions.iter().position(|region| region.intersects(rect)) {
+        rect = rect.union(regions.swap_remove(index));
+    }
+    regions.push(rect);
+}
+
+fn uses_texture(shape: &Shape, id: TextureId) -> bool {
+    match shape {
+        Shape::Vec(shapes) => shapes.iter().any(|shape| uses_texture(shape, id)),
+        Shape::Text(_) => id == TextureId::default(),
+        shape => shape.texture_id() == id,
+    }
+}
+
+fn has_callback(shape: &Shape) -> bool {
+    match shape {
+        Shape::Vec(shapes) => shapes.iter().any(has_callback),
+        Shape::Callback(_) => true,
+        _ => false,
+    }
+}
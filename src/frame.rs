@@ -0,0 +1,134 @@
+//! Frame output with the shapes already tessellated.
+
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use egui::epaint::{AlphaFromCoverage, ClippedPrimitive, ClippedShape, Tessellator, TextureAtlas};
+use egui::{Context, FullOutput, PlatformOutput, TexturesDelta, UserData};
+
+/// Everything a renderer needs to paint a frame. Returned by
+/// [`crate::Platform::end_pass_tessellated`].
+#[derive(Clone)]
+pub struct FrameOutput {
+    /// The tessellated shapes.
+    pub primitives: Vec<ClippedPrimitive>,
+    /// The texture changes, which have to be applied before painting the primitives.
+    pub textures_delta: TexturesDelta,
+    /// The size of the screen in physical pixel.
+    pub screen_size_in_pixels: [u32; 2],
+    /// The number of physical pixels per point the primitives were tessellated for.
+    pub pixels_per_point: f32,
+    /// The platform output, whose cursor, clipboard and link commands were already applied.
+    pub platform_output: PlatformOutput,
+    /// The screenshots requested by egui. See [`crate::Platform::deliver_screenshot`].
+    pub screenshot_requests: Vec<UserData>,
+    /// The time after which egui wants to be repainted. [`Duration::MAX`] if egui doesn't need
+    /// another frame until the next input.
+    pub repaint_delay: Duration,
+}
+
+impl FrameOutput {
+    /// Splits the output of egui into the frame without primitives and the shapes that still
+    /// have to be tessellated.
+    pub(crate) fn new(
+        context: &Context,
+        output: FullOutput,
+        screenshot_requests: Vec<UserData>,
+    ) -> (Self, Vec<ClippedShape>) {
+        let repaint_delay = output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map_or(Duration::MAX, |viewport| viewport.repaint_delay);
+        let frame = Self {
+            primitives: Vec::new(),
+            textures_delta: output.textures_delta,
+            screen_size_in_pixels: screen_size_in_pixels(context, output.pixels_per_point),
+            pixels_per_point: output.pixels_per_point,
+            platform_output: output.platform_output,
+            screenshot_requests,
+            repaint_delay,
+        };
+        (frame, output.shapes)
+    }
+}
+
+/// A frame that is tessellated on a worker thread. Returned by
+/// [`crate::Platform::end_pass_tessellate_async`].
+pub struct PendingFrame {
+    tessellation: JoinHandle<Vec<ClippedPrimitive>>,
+    frame: FrameOutput,
+}
+
+impl PendingFrame {
+    pub(crate) fn spawn(
+        context: &Context,
+        output: FullOutput,
+        screenshot_requests: Vec<UserData>,
+        disc_atlas: &mut DiscAtlas,
+    ) -> Self {
+        let (frame, shapes) = FrameOutput::new(context, output, screenshot_requests);
+        // The tessellator is set up here, so that the worker doesn't lock the context.
+        let mut tessellator = disc_atlas.tessellator(context, frame.pixels_per_point);
+        Self {
+            tessellation: std::thread::spawn(move || tessellator.tessellate_shapes(shapes)),
+            frame,
+        }
+    }
+
+    /// Returns `true` if the tessellation is done and [`Self::wait`] won't block.
+    pub fn is_finished(&self) -> bool {
+        self.tessellation.is_finished()
+    }
+
+    /// Waits for the tessellation to finish.
+    pub fn wait(self) -> FrameOutput {
+        let primitives = match self.tessellation.join() {
+            Ok(primitives) => primitives,
+            Err(panic) => std::panic::resume_unwind(panic),
+        };
+        FrameOutput {
+            primitives,
+            ..self.frame
+        }
+    }
+}
+
+/// Sets up tessellators like [`Context::tessellate`] does, but without keeping the context
+/// locked.
+///
+/// Egui doesn't expose the discs that are prerasterized into its font atlas. They are the first
+/// allocations of the atlas and only depend on its width, so they are taken from a second atlas
+/// of the same width.
+#[derive(Default)]
+pub(crate) struct DiscAtlas {
+    atlas: Option<TextureAtlas>,
+}
+
+impl DiscAtlas {
+    pub(crate) fn tessellator(&mut self, context: &Context, pixels_per_point: f32) -> Tessellator {
+        let options = context.tessellation_options(|options| *options);
+        let font_tex_size = context.fonts(|fonts| fonts.font_image_size());
+        let atlas = match &mut self.atlas {
+            Some(atlas) if atlas.size()[0] == font_tex_size[0] => atlas,
+            atlas => atlas.insert(TextureAtlas::new(
+                [font_tex_size[0], 1],
+                AlphaFromCoverage::default(),
+            )),
+        };
+
+        // The UV coordinates are normalized, so they are scaled to the height of egui's atlas.
+        let scale = atlas.size()[1] as f32 / font_tex_size[1] as f32;
+        let mut prepared_discs = atlas.prepared_discs();
+        for disc in &mut prepared_discs {
+            disc.uv.min.y *= scale;
+            disc.uv.max.y *= scale;
+        }
+        Tessellator::new(pixels_per_point, options, font_tex_size, prepared_discs)
+    }
+}
+
+/// Returns the size of the viewport in physical pixel.
+pub(crate) fn screen_size_in_pixels(context: &Context, pixels_per_point: f32) -> [u32; 2] {
+    let size = context.viewport_rect().size() * pixels_per_point;
+    [size.x.round() as u32, size.y.round() as u32]
+}
@@ -0,0 +1,169 @@
+//! Emulation of mouse gestures from touch events.
+
+use egui::{Event, Modifiers, Pos2, Vec2};
+use winit::event::TouchPhase;
+
+/// Configures which mouse gestures are emulated from touch events.
+///
+/// The gestures are emulated in addition to the pointer emulation selected with
+/// [`crate::TouchPointerEmulation`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchGestureEmulation {
+    /// Seconds a single finger has to be held without moving to send a secondary click.
+    /// `None` disables the long press. Defaults to 1 second.
+    ///
+    /// The long press is only detected if the time is updated with
+    /// [`crate::Platform::update_time`]. The duration should be longer than egui's
+    /// `max_click_duration`, otherwise lifting the finger also results in a primary click.
+    pub long_press_duration: Option<f64>,
+    /// Distance in points a finger may move while still counting as long press.
+    pub long_press_tolerance: f32,
+    /// Dragging two fingers sends mouse wheel events.
+    pub two_finger_scroll: bool,
+}
+
+impl Default for TouchGestureEmulation {
+    fn default() -> Self {
+        Self {
+            long_press_duration: Some(1.0),
+            long_press_tolerance: 8.0,
+            two_finger_scroll: true,
+        }
+    }
+}
+
+/// Identifies a finger by its egui device ID and winit touch ID.
+pub(crate) type Finger = (u64, u64);
+
+struct LongPress {
+    finger: Finger,
+    start_pos: Pos2,
+    start_time: f64,
+}
+
+/// Tracks the fingers on the screen to detect the gestures.
+pub(crate) struct TouchGestures {
+    config: TouchGestureEmulation,
+    fingers: Vec<(Finger, Pos2)>,
+    long_press: Option<LongPress>,
+}
+
+impl TouchGestures {
+    pub(crate) fn new(config: TouchGestureEmulation) -> Self {
+        Self {
+            config,
+            fingers: Vec::new(),
+            long_press: None,
+        }
+    }
+
+    /// Updates the gesture state with the given touch. Returns `true` if the touch is part
+    /// of a two finger scroll, in which case it shouldn't move the emulated pointer.
+    pub(crate) fn handle_touch(
+        &mut self,
+        finger: Finger,
+        phase: TouchPhase,
+        pos: Pos2,
+        time: Option<f64>,
+        modifiers: Modifiers,
+        events: &mut Vec<Event>,
+    ) -> bool {
+        let centroid_before = self.two_finger_centroid();
+
+        match phase {
+            TouchPhase::Started => {
+                self.fingers.push((finger, pos));
+            }
+            TouchPhase::Moved => {
+                if let Some((_, finger_pos)) = self.fingers.iter_mut().find(|(f, _)| *f == finger) {
+                    *finger_pos = pos;
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.fingers.retain(|(f, _)| *f != finger);
+            }
+        }
+
+        self.long_press = match (self.long_press.take(), self.fingers.as_slice()) {
+            (Some(long_press), [(f, pos)])
+                if *f == long_press.finger
+                    && (*pos - long_press.start_pos).length()
+                        <= self.config.long_press_tolerance =>
+            {
+                Some(long_press)
+            }
+            (None, [(f, pos)]) if phase == TouchPhase::Started => {
+                match (time, self.config.long_press_duration) {
+                    (Some(start_time), Some(_)) => Some(LongPress {
+                        finger: *f,
+                        start_pos: *pos,
+                        start_time,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        match (centroid_before, self.two_finger_centroid()) {
+            (Some(before), Some(after)) if phase == TouchPhase::Moved => {
+                let delta: Vec2 = after - before;
+                if delta != Vec2::ZERO {
+                    events.push(Event::MouseWheel {
+                        unit: egui::MouseWheelUnit::Point,
+                        delta,
+                        modifiers,
+                    });
+                }
+                true
+            }
+            (_, after) => after.is_some(),
+        }
+    }
+
+    /// Sends the secondary click once a long press was held long enough. Returns the
+    /// seconds until the pending long press is due.
+    pub(crate) fn update(
+        &mut self,
+        time: Option<f64>,
+        modifiers: Modifiers,
+        events: &mut Vec<Event>,
+    ) -> Option<f64> {
+        let (long_press, duration, time) =
+            match (&self.long_press, self.config.long_press_duration, time) {
+                (Some(long_press), Some(duration), Some(time)) => (long_press, duration, time),
+                _ => return None,
+            };
+
+        let remaining = long_press.start_time + duration - time;
+        if remaining > 0.0 {
+            return Some(remaining);
+        }
+
+        let pos = self
+            .fingers
+            .iter()
+            .find(|(f, _)| *f == long_press.finger)
+            .map_or(long_press.start_pos, |(_, pos)| *pos);
+        for pressed in [true, false] {
+            events.push(Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Secondary,
+                pressed,
+                modifiers,
+            });
+        }
+        self.long_press = None;
+
+        None
+    }
+
+    /// Returns the center of the fingers if exactly two fingers are down and two finger
+    /// scrolling is enabled.
+    fn two_finger_centroid(&self) -> Option<Pos2> {
+        match self.fingers.as_slice() {
+            [(_, a), (_, b)] if self.config.two_finger_scroll => Some(a.lerp(*b, 0.5)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Queues input from other threads until the next frame.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use egui::Context;
+
+use crate::KeyInput;
+
+/// The number of inputs that are queued at most by default.
+pub(crate) const DEFAULT_CAPACITY: usize = 1024;
+
+/// An input that waits for the next frame.
+pub(crate) enum QueuedInput {
+    Event(egui::Event),
+    Key(KeyInput),
+    DroppedFile(egui::DroppedFile),
+}
+
+pub(crate) struct InputQueue {
+    inputs: VecDeque<QueuedInput>,
+    capacity: usize,
+}
+
+impl InputQueue {
+    pub(crate) fn new() -> Arc<Mutex<Self>> {
+        Arc::new(Mutex::new(Self {
+            inputs: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+        }))
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Removes the queued inputs in the order they were sent.
+    pub(crate) fn drain(&mut self) -> std::collections::vec_deque::Drain<'_, QueuedInput> {
+        self.inputs.drain(..)
+    }
+}
+
+/// Locks the queue. The queue stays usable if a thread panicked while holding the lock.
+pub(crate) fn lock(queue: &Mutex<InputQueue>) -> MutexGuard<'_, InputQueue> {
+    queue.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Sends input to egui from any thread. Created by [`crate::Platform::input_sender`].
+///
+/// The input is queued and added to the [`egui::RawInput`] in [`crate::Platform::begin_pass`],
+/// in the order it was sent and after the input of winit's events. Sending requests a repaint,
+/// see [`crate::Platform::set_repaint_proxy`] to wake a waiting event loop.
+#[derive(Clone)]
+pub struct InputSender {
+    queue: Arc<Mutex<InputQueue>>,
+    context: Context,
+}
+
+impl InputSender {
+    pub(crate) fn new(queue: Arc<Mutex<InputQueue>>, context: Context) -> Self {
+        Self { queue, context }
+    }
+
+    /// Queues an egui event.
+    pub fn send_event(&self, event: egui::Event) -> Result<(), InputQueueFull> {
+        self.send(QueuedInput::Event(event))
+    }
+
+    /// Queues a key press or release. It's handled like [`crate::Platform::handle_key_input`],
+    /// so it updates the held modifiers and applies the keymap.
+    pub fn send_key(&self, input: KeyInput) -> Result<(), InputQueueFull> {
+        self.send(QueuedInput::Key(input))
+    }
+
+    /// Queues a file that was dropped onto the window.
+    pub fn send_dropped_file(&self, file: egui::DroppedFile) -> Result<(), InputQueueFull> {
+        self.send(QueuedInput::DroppedFile(file))
+    }
+
+    fn send(&self, input: QueuedInput) -> Result<(), InputQueueFull> {
+        {
+            let mut queue = lock(&self.queue);
+            if queue.inputs.len() >= queue.capacity {
+                return Err(InputQueueFull);
+            }
+            queue.inputs.push_back(input);
+        }
+        self.context.request_repaint();
+        Ok(())
+    }
+}
+
+/// The input wasn't sent because the queue of the [`InputSender`] is full. The queue is emptied
+/// at the beginning of each frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InputQueueFull;
+
+impl fmt::Display for InputQueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The input queue is full")
+    }
+}
+
+impl std::error::Error for InputQueueFull {}
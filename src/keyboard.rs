@@ -0,0 +1,243 @@
+//! Keyboard input that can also be created outside of winit.
+
+use winit::event::{ElementState, KeyEvent, Modifiers};
+use winit::keyboard::{
+    Key, KeyLocation, ModifiersKeyState, ModifiersState, NamedKey, PhysicalKey, SmolStr,
+};
+
+/// A key press or release, like [`winit::event::KeyEvent`].
+///
+/// Winit's key events can't be created outside of winit, so this type allows to
+/// simulate keyboard input with [`crate::Platform::handle_key_input`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyInput {
+    /// The position of the key on the keyboard.
+    pub physical_key: PhysicalKey,
+    /// The meaning of the key in the current keyboard layout, including modifiers.
+    pub logical_key: Key,
+    /// The text produced by the key press.
+    pub text: Option<SmolStr>,
+    /// The location of the key, e.g. for keys that exist on the left and the right side.
+    pub location: KeyLocation,
+    /// Whether the key was pressed or released.
+    pub state: ElementState,
+    /// Whether this is a repeated press of a held key.
+    pub repeat: bool,
+}
+
+impl From<&KeyEvent> for KeyInput {
+    fn from(event: &KeyEvent) -> Self {
+        Self {
+            physical_key: event.physical_key,
+            logical_key: event.logical_key.clone(),
+            text: event.text.clone(),
+            location: event.location,
+            state: event.state,
+            repeat: event.repeat,
+        }
+    }
+}
+
+/// The held modifier keys, separated by the side of the keyboard.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModifierKeys {
+    /// The left shift key is held.
+    pub left_shift: bool,
+    /// The right shift key is held.
+    pub right_shift: bool,
+    /// The left control key is held.
+    pub left_control: bool,
+    /// The right control key is held.
+    pub right_control: bool,
+    /// The left alt key is held.
+    pub left_alt: bool,
+    /// The right alt key is held. On layouts with AltGr this is the AltGr key, which Windows
+    /// reports as right alt together with left control.
+    pub right_alt: bool,
+    /// The left super key is held. This is the command key on macOS and the windows key elsewhere.
+    pub left_super: bool,
+    /// The right super key is held.
+    pub right_super: bool,
+}
+
+impl ModifierKeys {
+    /// Returns the held modifiers regardless of their side.
+    pub fn state(&self) -> ModifiersState {
+        let mut state = ModifiersState::empty();
+        state.set(ModifiersState::SHIFT, self.left_shift || self.right_shift);
+        state.set(
+            ModifiersState::CONTROL,
+            self.left_control || self.right_control,
+        );
+        state.set(ModifiersState::ALT, self.left_alt || self.right_alt);
+        state.set(ModifiersState::SUPER, self.left_super || self.right_super);
+        state
+    }
+}
+
+/// Tracks the held modifier keys and which of their sides were only guessed.
+#[derive(Default)]
+pub(crate) struct ModifierTracker {
+    keys: ModifierKeys,
+    // The sides that were assumed because an event didn't report them.
+    guessed: ModifierKeys,
+}
+
+impl ModifierTracker {
+    pub(crate) fn keys(&self) -> ModifierKeys {
+        self.keys
+    }
+
+    /// Updates the keys with a `ModifiersChanged` event. Not every platform reports the side
+    /// of the modifiers. If it is unknown, the side of the last key press is kept, or the left
+    /// key is assumed until a key event reports the side.
+    pub(crate) fn update(&mut self, modifiers: &Modifiers) {
+        let state = modifiers.state();
+        let update = |held: bool,
+                      left_state: ModifiersKeyState,
+                      right_state: ModifiersKeyState,
+                      [left, right]: [&mut bool; 2],
+                      [left_guessed, right_guessed]: [&mut bool; 2]| {
+            let left_known = left_state == ModifiersKeyState::Pressed;
+            let right_known = right_state == ModifiersKeyState::Pressed;
+            if !held {
+                *left = false;
+                *right = false;
+            } else if left_known || right_known {
+                *left = left_known;
+                *right = right_known;
+            } else if !*left && !*right {
+                *left = true;
+                *left_guessed = true;
+                return;
+            } else {
+                return;
+            }
+            *left_guessed = false;
+            *right_guessed = false;
+        };
+
+        let (keys, guessed) = (&mut self.keys, &mut self.guessed);
+        update(
+            state.shift_key(),
+            modifiers.lshift_state(),
+            modifiers.rshift_state(),
+            [&mut keys.left_shift, &mut keys.right_shift],
+            [&mut guessed.left_shift, &mut guessed.right_shift],
+        );
+        update(
+            state.control_key(),
+            modifiers.lcontrol_state(),
+            modifiers.rcontrol_state(),
+            [&mut keys.left_control, &mut keys.right_control],
+            [&mut guessed.left_control, &mut guessed.right_control],
+        );
+        update(
+            state.alt_key(),
+            modifiers.lalt_state(),
+            modifiers.ralt_state(),
+            [&mut keys.left_alt, &mut keys.right_alt],
+            [&mut guessed.left_alt, &mut guessed.right_alt],
+        );
+        update(
+            state.super_key(),
+            modifiers.lsuper_state(),
+            modifiers.rsuper_state(),
+            [&mut keys.left_super, &mut keys.right_super],
+            [&mut guessed.left_super, &mut guessed.right_super],
+        );
+    }
+
+    /// Updates the keys with a key press or release. Returns `true` if the key is a
+    /// modifier key.
+    pub(crate) fn update_key(&mut self, input: &KeyInput) -> bool {
+        let (keys, guessed) = (&mut self.keys, &mut self.guessed);
+        let (left, right, left_guessed, right_guessed) = match input.logical_key {
+            Key::Named(NamedKey::Shift) => (
+                &mut keys.left_shift,
+                &mut keys.right_shift,
+                &mut guessed.left_shift,
+                &mut guessed.right_shift,
+            ),
+            Key::Named(NamedKey::Control) => (
+                &mut keys.left_control,
+                &mut keys.right_control,
+                &mut guessed.left_control,
+                &mut guessed.right_control,
+            ),
+            Key::Named(NamedKey::Alt) => (
+                &mut keys.left_alt,
+                &mut keys.right_alt,
+                &mut guessed.left_alt,
+                &mut guessed.right_alt,
+            ),
+            Key::Named(NamedKey::Super | NamedKey::Meta) => (
+                &mut keys.left_super,
+                &mut keys.right_super,
+                &mut guessed.left_super,
+                &mut guessed.right_super,
+            ),
+            _ => return false,
+        };
+
+        let pressed = input.state == ElementState::Pressed;
+        match input.location {
+            KeyLocation::Left | KeyLocation::Right => {
+                let (key, key_guessed, other, other_guessed) =
+                    if input.location == KeyLocation::Right {
+                        (right, right_guessed, left, left_guessed)
+                    } else {
+                        (left, left_guessed, right, right_guessed)
+                    };
+                *key = pressed;
+                *key_guessed = false;
+                // A side that was guessed by a `ModifiersChanged` event before this key event
+                // was the wrong one.
+                if *other_guessed {
+                    *other = false;
+                    *other_guessed = false;
+                }
+            }
+            _ => {
+                *left = pressed;
+                *left_guessed = pressed;
+            }
+        }
+        true
+    }
+}
+
+/// Returns the key input of pressing and releasing the key. The physical key is the key of a
+/// US keyboard layout that has the logical key on it, if there's one.
+#[cfg(any(feature = "automation", feature = "testing"))]
+pub(crate) fn key_presses(key: Key) -> [KeyInput; 2] {
+    let text = match &key {
+        Key::Character(text)
+            if text.chars().count() == 1 && !text.chars().all(char::is_control) =>
+        {
+            Some(text.clone())
+        }
+        Key::Named(NamedKey::Space) => Some(SmolStr::new(" ")),
+        _ => None,
+    };
+    let physical_key = crate::winit_to_egui_key_code(&key)
+        .and_then(crate::egui_to_winit_key_code)
+        .map_or(
+            PhysicalKey::Unidentified(winit::keyboard::NativeKeyCode::Unidentified),
+            PhysicalKey::Code,
+        );
+    let press = KeyInput {
+        physical_key,
+        logical_key: key,
+        text,
+        location: KeyLocation::Standard,
+        state: ElementState::Pressed,
+        repeat: false,
+    };
+    let release = KeyInput {
+        text: None,
+        state: ElementState::Released,
+        ..press.clone()
+    };
+    [press, release]
+}
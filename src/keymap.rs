@@ -0,0 +1,290 @@
+//! Configurable keyboard shortcuts.
+
+use std::fmt;
+use std::path::Path;
+
+use egui::Key;
+use winit::keyboard::{ModifiersState, PhysicalKey};
+
+use crate::{winit_to_egui_key_code, winit_to_egui_physical_key};
+
+/// A key of a [`KeyChord`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ChordKey {
+    /// The key is matched by its meaning in the current keyboard layout.
+    Logical(Key),
+    /// The key is matched by its position on the keyboard, named after the key at
+    /// this position on a US keyboard layout.
+    Physical(Key),
+}
+
+/// A key together with the modifiers that have to be held.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    /// The pressed key.
+    pub key: ChordKey,
+    /// The modifiers that have to be held. Other modifiers must not be held.
+    pub modifiers: ModifiersState,
+}
+
+impl KeyChord {
+    /// Creates a chord of a logical key.
+    pub fn logical(modifiers: ModifiersState, key: Key) -> Self {
+        Self {
+            key: ChordKey::Logical(key),
+            modifiers,
+        }
+    }
+
+    /// Creates a chord of a physical key.
+    pub fn physical(modifiers: ModifiersState, key: Key) -> Self {
+        Self {
+            key: ChordKey::Physical(key),
+            modifiers,
+        }
+    }
+
+    fn matches(
+        &self,
+        logical_key: &winit::keyboard::Key,
+        physical_key: PhysicalKey,
+        modifiers: ModifiersState,
+    ) -> bool {
+        let modifiers = modifiers
+            & (ModifiersState::CONTROL
+                | ModifiersState::SHIFT
+                | ModifiersState::ALT
+                | ModifiersState::SUPER);
+        let (key, chord_key) = match self.key {
+            ChordKey::Logical(key) => (winit_to_egui_key_code(logical_key), key),
+            ChordKey::Physical(key) => (winit_to_egui_physical_key(physical_key), key),
+        };
+
+        modifiers == self.modifiers && key == Some(chord_key)
+    }
+}
+
+/// The action that is triggered by a [`KeyChord`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    /// Sends [`egui::Event::Copy`].
+    Copy,
+    /// Sends [`egui::Event::Cut`].
+    Cut,
+    /// Pastes the content of the clipboard.
+    Paste,
+    /// Sends the key egui uses for undo.
+    Undo,
+    /// Sends the key egui uses for redo.
+    Redo,
+    /// Sends the key egui uses to select all.
+    SelectAll,
+    /// An action of the application, which can be retrieved with
+    /// [`crate::Platform::take_triggered_actions`].
+    User(String),
+}
+
+/// Maps key chords to actions. The first matching binding is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, KeyAction)>,
+}
+
+impl Default for Keymap {
+    /// Creates the keymap with the shortcuts of the current platform.
+    fn default() -> Self {
+        let command = if cfg!(target_os = "macos") {
+            ModifiersState::SUPER
+        } else {
+            ModifiersState::CONTROL
+        };
+
+        let mut keymap = Self::empty();
+        keymap.bind(KeyChord::logical(command, Key::C), KeyAction::Copy);
+        keymap.bind(KeyChord::logical(command, Key::X), KeyAction::Cut);
+        keymap.bind(KeyChord::logical(command, Key::V), KeyAction::Paste);
+        if !cfg!(target_os = "macos") {
+            keymap.bind(
+                KeyChord::logical(ModifiersState::CONTROL, Key::Insert),
+                KeyAction::Copy,
+            );
+            keymap.bind(
+                KeyChord::logical(ModifiersState::SHIFT, Key::Insert),
+                KeyAction::Paste,
+            );
+            keymap.bind(
+                KeyChord::logical(ModifiersState::SHIFT, Key::Delete),
+                KeyAction::Cut,
+            );
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Creates a keymap without any bindings.
+    pub fn empty() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Binds the chord to the action. Replaces an existing binding of the chord.
+    pub fn bind(&mut self, chord: KeyChord, action: KeyAction) {
+        self.unbind(&chord);
+        self.bindings.push((chord, action));
+    }
+
+    /// Removes the binding of the chord.
+    pub fn unbind(&mut self, chord: &KeyChord) {
+        self.bindings.retain(|(bound, _)| bound != chord);
+    }
+
+    /// Returns all bindings.
+    pub fn bindings(&self) -> impl Iterator<Item = (&KeyChord, &KeyAction)> {
+        self.bindings.iter().map(|(chord, action)| (chord, action))
+    }
+
+    /// Returns the action bound to the pressed key.
+    pub fn action(
+        &self,
+        logical_key: &winit::keyboard::Key,
+        physical_key: PhysicalKey,
+        modifiers: ModifiersState,
+    ) -> Option<&KeyAction> {
+        self.bindings
+            .iter()
+            .find(|(chord, _)| chord.matches(logical_key, physical_key, modifiers))
+            .map(|(_, action)| action)
+    }
+
+    /// Loads a keymap from the file at the given path. See [`Self::parse`] for the format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeymapError> {
+        let text = std::fs::read_to_string(path).map_err(KeymapError::Io)?;
+        Self::parse(&text)
+    }
+
+    /// Parses a keymap. Every line binds a chord to an action:
+    ///
+    /// ```text
+    /// # Comments start with '#'.
+    /// Ctrl+C = copy
+    /// Shift+Insert = paste
+    /// Command+Shift+Z = redo
+    /// Ctrl+Physical(S) = user save
+    /// ```
+    ///
+    /// Keys use the names of [`egui::Key`]. `Physical(..)` matches the key by its position.
+    /// The modifiers are `Ctrl`, `Shift`, `Alt`, `Super` (also `Cmd`) and `Command`, which is
+    /// `Cmd` on macOS and `Ctrl` elsewhere. The actions are `copy`, `cut`, `paste`, `undo`,
+    /// `redo`, `select_all` and `user <name>`.
+    pub fn parse(text: &str) -> Result<Self, KeymapError> {
+        let mut keymap = Self::empty();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| KeymapError::Parse {
+                line: index + 1,
+                message,
+            };
+
+            let (chord, action) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected '<chord> = <action>'".to_owned()))?;
+            let chord = parse_chord(chord.trim()).map_err(error)?;
+            let action = parse_action(action.trim()).map_err(error)?;
+            keymap.bind(chord, action);
+        }
+
+        Ok(keymap)
+    }
+}
+
+fn parse_chord(text: &str) -> Result<KeyChord, String> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    // Allows to bind the plus key itself, e.g. "Ctrl++".
+    if text.ends_with("++") {
+        parts.truncate(parts.len() - 2);
+        parts.push("+");
+    }
+    let key = parts.pop().filter(|key| !key.is_empty());
+    let key = key.ok_or_else(|| format!("missing key in '{}'", text))?;
+
+    let mut modifiers = ModifiersState::empty();
+    for modifier in parts {
+        modifiers |= match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => ModifiersState::CONTROL,
+            "shift" => ModifiersState::SHIFT,
+            "alt" | "option" => ModifiersState::ALT,
+            "super" | "cmd" | "meta" | "win" => ModifiersState::SUPER,
+            "command" if cfg!(target_os = "macos") => ModifiersState::SUPER,
+            "command" => ModifiersState::CONTROL,
+            _ => return Err(format!("unknown modifier '{}'", modifier)),
+        };
+    }
+
+    let parse_key =
+        |name: &str| Key::from_name(name).ok_or_else(|| format!("unknown key '{}'", name));
+    let key = match key
+        .strip_prefix("Physical(")
+        .and_then(|key| key.strip_suffix(')'))
+    {
+        Some(key) => ChordKey::Physical(parse_key(key.trim())?),
+        None => ChordKey::Logical(parse_key(key)?),
+    };
+
+    Ok(KeyChord { key, modifiers })
+}
+
+fn parse_action(text: &str) -> Result<KeyAction, String> {
+    Ok(match text {
+        "copy" => KeyAction::Copy,
+        "cut" => KeyAction::Cut,
+        "paste" => KeyAction::Paste,
+        "undo" => KeyAction::Undo,
+        "redo" => KeyAction::Redo,
+        "select_all" => KeyAction::SelectAll,
+        _ => match text.strip_prefix("user ") {
+            Some(name) if !name.trim().is_empty() => KeyAction::User(name.trim().to_owned()),
+            _ => return Err(format!("unknown action '{}'", text)),
+        },
+    })
+}
+
+/// An error while loading a [`Keymap`].
+#[derive(Debug)]
+pub enum KeymapError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// A line of the keymap is invalid.
+    Parse {
+        /// The number of the line, starting at 1.
+        line: usize,
+        /// Describes the problem.
+        message: String,
+    },
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Io(err) => write!(f, "Can't read keymap: {}", err),
+            KeymapError::Parse { line, message } => {
+                write!(f, "Invalid keymap in line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KeymapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KeymapError::Io(err) => Some(err),
+            KeymapError::Parse { .. } => None,
+        }
+    }
+}
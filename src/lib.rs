@@ -5,7 +5,54 @@
 //! A basic usage example can be found [here](https://github.com/hasenbanck/egui_example).
 #![warn(missing_docs)]
 
-use std::collections::HashMap;
+#[cfg(any(feature = "automation", feature = "testing"))]
+mod access_tree;
+mod app_input;
+#[cfg(feature = "automation")]
+mod automation;
+mod capture;
+mod cursor;
+mod damage;
+mod frame;
+mod gestures;
+mod input_sender;
+mod keyboard;
+mod keymap;
+#[cfg(feature = "persistence")]
+mod persistence;
+mod repaint;
+#[cfg(feature = "runner")]
+mod runner;
+#[cfg(feature = "soft_render")]
+mod soft_render;
+#[cfg(feature = "testing")]
+mod testing;
+mod textures;
+mod window_geometry;
+
+pub use app_input::AppInputState;
+#[cfg(feature = "automation")]
+pub use automation::{AutomationAddress, AutomationClient};
+pub use capture::{EventResponse, InputOwner};
+pub use frame::{FrameOutput, PendingFrame};
+pub use gestures::TouchGestureEmulation;
+pub use input_sender::{InputQueueFull, InputSender};
+pub use keyboard::{KeyInput, ModifierKeys};
+pub use keymap::{ChordKey, KeyAction, KeyChord, Keymap, KeymapError};
+#[cfg(feature = "persistence")]
+pub use persistence::Storage;
+pub use repaint::RepaintEvent;
+#[cfg(feature = "runner")]
+pub use runner::{App, HeadlessRenderer, Renderer, Runner};
+#[cfg(feature = "soft_render")]
+pub use soft_render::SoftRenderer;
+#[cfg(feature = "testing")]
+pub use testing::{Widget, WidgetInfo};
+pub use textures::{ManagedTexture, TextureBackend, TextureManager};
+pub use window_geometry::WindowGeometry;
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
 
 #[cfg(feature = "clipboard")]
 use copypasta::{ClipboardContext, ClipboardProvider};
@@ -14,16 +61,36 @@ use egui::{
     Context, Key, MouseWheelUnit, Pos2,
 };
 use winit::event::MouseButton;
-use winit::keyboard::{ModifiersState, NamedKey};
+use winit::keyboard::{KeyCode, ModifiersState, NamedKey};
 use winit::{
     dpi::PhysicalSize,
     event::{
         TouchPhase,
         WindowEvent::{self, *},
     },
-    window::CursorIcon,
+    window::{CursorIcon, CustomCursor},
 };
 
+#[cfg(feature = "accesskit")]
+pub use accesskit_winit;
+
+/// Configures how touch events are translated into pointer events.
+///
+/// Egui always receives the raw touch events. Since most egui widgets only react to
+/// pointer events, touches are additionally used to emulate a pointer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TouchPointerEmulation {
+    /// All fingers are merged into a single virtual pointer, which stays pressed
+    /// until the last finger is lifted.
+    #[default]
+    Merged,
+    /// Only the first finger that touches the screen moves and presses the pointer.
+    /// Other fingers are ignored until it is lifted.
+    FirstFinger,
+    /// Touches don't emulate a pointer.
+    Disabled,
+}
+
 /// Configures the creation of the `Platform`.
 #[derive(Debug, Default)]
 pub struct PlatformDescriptor {
@@ -37,6 +104,18 @@ pub struct PlatformDescriptor {
     pub font_definitions: egui::FontDefinitions,
     /// Egui style configuration.
     pub style: egui::Style,
+    /// How touch events emulate pointer events.
+    pub touch_pointer_emulation: TouchPointerEmulation,
+    /// Which mouse gestures are emulated from touch events. `None` disables the emulation.
+    pub touch_gesture_emulation: Option<TouchGestureEmulation>,
+    /// Keyboard shortcuts. Defaults to the shortcuts of the current platform.
+    pub keymap: Keymap,
+    /// Decides which characters of the text input are send to egui. `None` drops control
+    /// characters and characters of the Unicode private use areas.
+    pub text_filter: Option<fn(char) -> bool>,
+    /// Compares each frame with the last one to find the regions that have to be redrawn,
+    /// see [`Platform::damage_regions`].
+    pub damage_tracking: bool,
 }
 
 #[cfg(feature = "webbrowser")]
@@ -53,7 +132,10 @@ fn handle_links(output: &egui::PlatformOutput) {
 }
 
 #[cfg(feature = "clipboard")]
-fn handle_clipboard(output: &egui::PlatformOutput, clipboard: Option<&mut ClipboardContext>) {
+fn handle_clipboard(
+    output: &egui::PlatformOutput,
+    clipboard: Option<&mut Box<dyn ClipboardProvider>>,
+) {
     if let Some(clipboard) = clipboard {
         for command in &output.commands {
             if let egui::OutputCommand::CopyText(copied_text) = command {
@@ -71,14 +153,65 @@ pub struct Platform {
     context: Context,
     raw_input: egui::RawInput,
     modifier_state: ModifiersState,
+    // The held modifiers by side. `modifier_state` is derived from them.
+    modifier_keys: keyboard::ModifierTracker,
     pointer_pos: Option<Pos2>,
 
+    // The last known pointer position, which is kept after the pointer left
+    // the window, so that button releases outside of the window can still be
+    // send to egui. The `PointerGone` event is delayed until all mouse buttons
+    // are released, so that drags don't get stuck.
+    last_pointer_pos: Option<Pos2>,
+    pressed_mouse_buttons: Vec<egui::PointerButton>,
+    pointer_gone_pending: bool,
+
+    // Cursor state requested by egui through viewport commands and cursor
+    // icons. It's re-applied after the window regains focus.
+    cursor: cursor::CursorState<CustomCursor>,
+
     #[cfg(feature = "clipboard")]
-    clipboard: Option<ClipboardContext>,
+    clipboard: Option<Box<dyn ClipboardProvider>>,
+
+    keymap: Keymap,
+    triggered_actions: Vec<String>,
+    text_filter: fn(char) -> bool,
+    // Keys whose press was replaced by a shortcut action, so that their release
+    // isn't send to egui either.
+    replaced_keys: HashSet<winit::keyboard::PhysicalKey>,
+
+    #[cfg(feature = "accesskit")]
+    accesskit: Option<accesskit_winit::Adapter>,
+    #[cfg(feature = "automation")]
+    automation: Option<automation::AutomationServer>,
+    #[cfg(any(feature = "automation", feature = "testing"))]
+    access_tree: access_tree::AccessTree,
 
-    // For emulating pointer events from touch events we merge multi-touch
-    // pointers, and ref-count the press state.
+    #[cfg(feature = "persistence")]
+    storage: Option<Storage>,
+    window_geometry: WindowGeometry,
+
+    // Screenshots requested by egui that the renderer hasn't taken yet.
+    screenshot_requests: Vec<egui::UserData>,
+
+    // Sets up the tessellators of asynchronously tessellated frames.
+    disc_atlas: frame::DiscAtlas,
+
+    // The regions that changed in the last frame, if damage tracking is enabled.
+    damage: Option<damage::DamageTracker>,
+    damage_regions: Vec<egui::Rect>,
+
+    // For emulating pointer events from touch events we either merge multi-touch
+    // pointers and ref-count the press state, or follow the first finger.
+    touch_pointer_emulation: TouchPointerEmulation,
     touch_pointer_pressed: u32,
+    touch_pointer_finger: Option<gestures::Finger>,
+    touch_gestures: Option<gestures::TouchGestures>,
+
+    // Ownership of the pressed mouse buttons, fingers and keys.
+    capture: capture::InputCapture,
+    app_input: AppInputState,
+    // Input sent from other threads through an `InputSender`.
+    input_queue: std::sync::Arc<std::sync::Mutex<input_sender::InputQueue>>,
 
     // Egui requires unique u64 device IDs for touch events but Winit's
     // device IDs are opaque, so we have to create our own ID mapping.
@@ -93,7 +226,7 @@ impl Platform {
 
         context.set_fonts(descriptor.font_definitions.clone());
         context.set_style(descriptor.style);
-        let raw_input = egui::RawInput {
+        let mut raw_input = egui::RawInput {
             screen_rect: Some(egui::Rect::from_min_size(
                 Pos2::default(),
                 vec2(
@@ -103,24 +236,135 @@ impl Platform {
             )),
             ..Default::default()
         };
+        raw_input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(descriptor.scale_factor as f32);
 
         Self {
             scale_factor: descriptor.scale_factor,
             context,
             raw_input,
             modifier_state: ModifiersState::empty(),
-            pointer_pos: Some(Pos2::default()),
+            modifier_keys: keyboard::ModifierTracker::default(),
+            pointer_pos: None,
+            last_pointer_pos: None,
+            pressed_mouse_buttons: Vec::new(),
+            pointer_gone_pending: false,
+            cursor: Default::default(),
             #[cfg(feature = "clipboard")]
-            clipboard: ClipboardContext::new().ok(),
+            clipboard: ClipboardContext::new()
+                .ok()
+                .map(|clipboard| Box::new(clipboard) as Box<dyn ClipboardProvider>),
+            keymap: descriptor.keymap,
+            triggered_actions: Vec::new(),
+            text_filter: descriptor.text_filter.unwrap_or(is_printable),
+            replaced_keys: HashSet::new(),
+            #[cfg(feature = "accesskit")]
+            accesskit: None,
+            #[cfg(feature = "automation")]
+            automation: None,
+            #[cfg(any(feature = "automation", feature = "testing"))]
+            access_tree: Default::default(),
+            #[cfg(feature = "persistence")]
+            storage: None,
+            window_geometry: WindowGeometry {
+                inner_size: [descriptor.physical_width, descriptor.physical_height],
+                scale_factor: descriptor.scale_factor,
+                ..Default::default()
+            },
+            screenshot_requests: Vec::new(),
+            disc_atlas: frame::DiscAtlas::default(),
+            damage: descriptor
+                .damage_tracking
+                .then(damage::DamageTracker::default),
+            damage_regions: Vec::new(),
+            touch_pointer_emulation: descriptor.touch_pointer_emulation,
             touch_pointer_pressed: 0,
+            touch_pointer_finger: None,
+            touch_gestures: descriptor
+                .touch_gesture_emulation
+                .map(gestures::TouchGestures::new),
+            capture: Default::default(),
+            app_input: Default::default(),
+            input_queue: input_sender::InputQueue::new(),
             device_indices: HashMap::new(),
             next_device_index: 1,
         }
     }
 
-    /// Handles the given winit window event and updates the egui context. Should be called before starting a new frame with `start_frame()`.
-    pub fn handle_event(&mut self, event: &WindowEvent) {
+    /// Creates the AccessKit adapter for the given window. Events of the adapter are send
+    /// through the `proxy` and should be passed to [`Self::handle_accesskit_event`].
+    ///
+    /// This must be called before the window is made visible for the first time.
+    #[cfg(feature = "accesskit")]
+    pub fn init_accesskit<T: From<accesskit_winit::Event> + Send>(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window: &winit::window::Window,
+        proxy: winit::event_loop::EventLoopProxy<T>,
+    ) {
+        self.accesskit = Some(accesskit_winit::Adapter::with_event_loop_proxy(
+            event_loop, window, proxy,
+        ));
+    }
+
+    /// Passes the given winit window event to the AccessKit adapter. Should be called
+    /// for every window event before it's handled by [`Self::handle_event`].
+    #[cfg(feature = "accesskit")]
+    pub fn process_accesskit_window_event(
+        &mut self,
+        window: &winit::window::Window,
+        event: &WindowEvent,
+    ) {
+        if let Some(adapter) = self.accesskit.as_mut() {
+            adapter.process_event(window, event);
+        }
+    }
+
+    /// Handles an event of the AccessKit adapter. Action requests are forwarded to egui
+    /// with the next frame.
+    #[cfg(feature = "accesskit")]
+    pub fn handle_accesskit_event(&mut self, event: &accesskit_winit::WindowEvent) {
         match event {
+            accesskit_winit::WindowEvent::InitialTreeRequested => {
+                self.context.enable_accesskit();
+                // The adapter needs a full tree, which egui only creates while drawing a frame.
+                self.context.request_repaint();
+            }
+            accesskit_winit::WindowEvent::ActionRequested(request) => {
+                self.raw_input
+                    .events
+                    .push(egui::Event::AccessKitActionRequest(request.clone()));
+                self.context.request_repaint();
+            }
+            accesskit_winit::WindowEvent::AccessibilityDeactivated => {
+                self.context.disable_accesskit();
+            }
+        }
+    }
+
+    /// Handles the given winit window event and updates the egui context. Should be called before starting a new frame with `start_frame()`.
+    ///
+    /// The returned [`EventResponse`] tells whether the event belongs to egui or the application,
+    /// and whether a frame should be drawn for it. Once a mouse button, finger or key was
+    /// pressed, all following events of it belong to the same receiver until it is released.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> EventResponse {
+        let finger = match event {
+            KeyboardInput { event, .. } => return self.handle_key_input(&KeyInput::from(event)),
+            Touch(touch) => Some((self.touch_device_index(touch.device_id), touch.id)),
+            _ => None,
+        };
+        let owner = self
+            .capture
+            .owner(&self.context, event, finger, self.scale_factor);
+        self.capture.update(event, finger, owner);
+        self.app_input.update(event, owner);
+
+        let events = self.raw_input.events.len();
+        // Whether the event changed the input of egui other than by sending an event.
+        let changed = match event {
             // Resize with 0 width and height is used by winit to signal a minimize event on Windows.
             // See: https://github.com/rust-windowing/winit/issues/208
             // There is nothing to do for minimize events, so it is ignored here. This solves an issue where
@@ -128,16 +372,61 @@ impl Platform {
             Resized(PhysicalSize {
                 width: 0,
                 height: 0,
-            }) => {}
+            }) => false,
             Resized(physical_size) => {
                 self.raw_input.screen_rect = Some(egui::Rect::from_min_size(
                     Default::default(),
                     vec2(physical_size.width as f32, physical_size.height as f32)
                         / self.scale_factor as f32,
                 ));
+                self.window_geometry.inner_size = [physical_size.width, physical_size.height];
+                true
+            }
+            Moved(position) => {
+                self.window_geometry.position = Some([position.x, position.y]);
+                false
             }
             ScaleFactorChanged { scale_factor, .. } => {
                 self.scale_factor = *scale_factor;
+                self.window_geometry.scale_factor = *scale_factor;
+                self.raw_input
+                    .viewports
+                    .entry(egui::ViewportId::ROOT)
+                    .or_default()
+                    .native_pixels_per_point = Some(*scale_factor as f32);
+                true
+            }
+            Focused(true) => {
+                self.reapply_cursor();
+                false
+            }
+            #[cfg(feature = "persistence")]
+            CloseRequested | Destroyed => {
+                if let Err(err) = self.save() {
+                    eprintln!("Can't save storage: {}", err);
+                }
+                false
+            }
+            Focused(false) => {
+                // Releases aren't send to unfocused windows.
+                self.replaced_keys.clear();
+                self.modifier_keys = keyboard::ModifierTracker::default();
+                self.update_modifier_state();
+                for button in std::mem::take(&mut self.pressed_mouse_buttons) {
+                    if let Some(pos) = self.pointer_pos.or(self.last_pointer_pos) {
+                        self.raw_input.events.push(egui::Event::PointerButton {
+                            pos,
+                            button,
+                            pressed: false,
+                            modifiers: Default::default(),
+                        });
+                    }
+                }
+                if self.pointer_gone_pending {
+                    self.pointer_gone_pending = false;
+                    self.raw_input.events.push(egui::Event::PointerGone);
+                }
+                true
             }
             MouseInput { state, button, .. } => {
                 if let Some(button) = match button {
@@ -146,100 +435,43 @@ impl Platform {
                     MouseButton::Middle => Some(egui::PointerButton::Middle),
                     _ => None,
                 } {
-                    // push event only if the cursor is inside the window
-                    if let Some(pointer_pos) = self.pointer_pos {
+                    let pressed = *state == winit::event::ElementState::Pressed;
+                    let was_pressed = self.pressed_mouse_buttons.contains(&button);
+
+                    // Presses are only send if the pointer position is known. Releases of
+                    // buttons that were pressed inside the window are always send, even if
+                    // the pointer has left the window in the meantime.
+                    let pointer_pos = if !pressed && was_pressed {
+                        self.pointer_pos.or(self.last_pointer_pos)
+                    } else {
+                        self.pointer_pos
+                    };
+
+                    if let Some(pointer_pos) = pointer_pos {
+                        if pressed && !was_pressed {
+                            self.pressed_mouse_buttons.push(button);
+                        } else if !pressed {
+                            self.pressed_mouse_buttons.retain(|b| *b != button);
+                        }
+
                         self.raw_input.events.push(egui::Event::PointerButton {
                             pos: pointer_pos,
                             button,
-                            pressed: *state == winit::event::ElementState::Pressed,
+                            pressed,
                             modifiers: Default::default(),
                         });
+
+                        if self.pointer_gone_pending && self.pressed_mouse_buttons.is_empty() {
+                            self.pointer_gone_pending = false;
+                            self.raw_input.events.push(egui::Event::PointerGone);
+                        }
                     }
                 }
+                false
             }
             Touch(touch) => {
-                let pointer_pos = pos2(
-                    touch.location.x as f32 / self.scale_factor as f32,
-                    touch.location.y as f32 / self.scale_factor as f32,
-                );
-
-                let device_id = match self.device_indices.get(&touch.device_id) {
-                    Some(id) => *id,
-                    None => {
-                        let device_id = self.next_device_index;
-                        self.device_indices.insert(touch.device_id, device_id);
-                        self.next_device_index += 1;
-                        device_id
-                    }
-                };
-                let egui_phase = match touch.phase {
-                    TouchPhase::Started => egui::TouchPhase::Start,
-                    TouchPhase::Moved => egui::TouchPhase::Move,
-                    TouchPhase::Ended => egui::TouchPhase::End,
-                    TouchPhase::Cancelled => egui::TouchPhase::Cancel,
-                };
-
-                let force = match touch.force {
-                    Some(winit::event::Force::Calibrated { force, .. }) => force as f32,
-                    Some(winit::event::Force::Normalized(force)) => force as f32,
-                    None => 0.0f32, // hmmm, egui can't differentiate unsupported from zero pressure
-                };
-
-                self.raw_input.events.push(egui::Event::Touch {
-                    device_id: egui::TouchDeviceId(device_id),
-                    id: egui::TouchId(touch.id),
-                    phase: egui_phase,
-                    pos: pointer_pos,
-                    force: Some(force),
-                });
-
-                // Currently Winit doesn't emulate pointer events based on
-                // touch events but Egui requires pointer emulation.
-                //
-                // For simplicity we just merge all touch pointers into a
-                // single virtual pointer and ref-count the press state
-                // (i.e. the pointer will remain pressed during multi-touch
-                // events until the last pointer is lifted up)
-
-                let was_pressed = self.touch_pointer_pressed > 0;
-
-                match touch.phase {
-                    TouchPhase::Started => {
-                        self.touch_pointer_pressed += 1;
-                    }
-                    TouchPhase::Ended | TouchPhase::Cancelled => {
-                        self.touch_pointer_pressed = self
-                            .touch_pointer_pressed
-                            .checked_sub(1).unwrap_or_else(|| {
-                            eprintln!("Pointer emulation error: Unbalanced touch start/stop events from Winit");
-                            0
-                        });
-                    }
-                    TouchPhase::Moved => {
-                        self.raw_input
-                            .events
-                            .push(egui::Event::PointerMoved(pointer_pos));
-                    }
-                }
-
-                if !was_pressed && self.touch_pointer_pressed > 0 {
-                    self.raw_input.events.push(egui::Event::PointerButton {
-                        pos: pointer_pos,
-                        button: egui::PointerButton::Primary,
-                        pressed: true,
-                        modifiers: Default::default(),
-                    });
-                } else if was_pressed && self.touch_pointer_pressed == 0 {
-                    // Egui docs say that the pressed=false should be sent _before_
-                    // the PointerGone.
-                    self.raw_input.events.push(egui::Event::PointerButton {
-                        pos: pointer_pos,
-                        button: egui::PointerButton::Primary,
-                        pressed: false,
-                        modifiers: Default::default(),
-                    });
-                    self.raw_input.events.push(egui::Event::PointerGone);
-                }
+                self.handle_touch(touch);
+                false
             }
             MouseWheel { delta, .. } => {
                 let (mut delta, unit) = match delta {
@@ -261,6 +493,7 @@ impl Platform {
                     modifiers: self.raw_input.modifiers,
                     delta,
                 });
+                false
             }
             CursorMoved { position, .. } => {
                 let pointer_pos = pos2(
@@ -268,78 +501,354 @@ impl Platform {
                     position.y as f32 / self.scale_factor as f32,
                 );
                 self.pointer_pos = Some(pointer_pos);
+                self.last_pointer_pos = Some(pointer_pos);
                 self.raw_input
                     .events
                     .push(egui::Event::PointerMoved(pointer_pos));
+                false
+            }
+            CursorEntered { .. } => {
+                // The pointer position is only known after the first `CursorMoved`.
+                self.pointer_gone_pending = false;
+                false
             }
             CursorLeft { .. } => {
                 self.pointer_pos = None;
-                self.raw_input.events.push(egui::Event::PointerGone);
+                if self.pressed_mouse_buttons.is_empty() {
+                    self.raw_input.events.push(egui::Event::PointerGone);
+                } else {
+                    self.pointer_gone_pending = true;
+                }
+                false
             }
             ModifiersChanged(input) => {
-                self.modifier_state = input.state();
-                self.raw_input.modifiers = winit_to_egui_modifiers(input.state());
-            }
-            KeyboardInput { event, .. } => {
-                let key = &event.logical_key;
-                let pressed = event.state == winit::event::ElementState::Pressed;
-                let ctrl = self.modifier_state.control_key();
-
-                if pressed
-                    && !self
-                        .modifier_state
-                        .intersects(ModifiersState::CONTROL | ModifiersState::SUPER)
-                {
-                    if let Some(ch) = &event.text {
-                        let str: String = ch.chars().filter(|c| is_printable(*c)).collect();
-                        if !str.is_empty() {
-                            self.raw_input.events.push(egui::Event::Text(str));
-                        }
+                self.modifier_keys.update(input);
+                self.update_modifier_state();
+                true
+            }
+            _ => false,
+        };
+
+        EventResponse {
+            owner,
+            repaint: changed || self.raw_input.events.len() > events,
+        }
+    }
+
+    /// Handles the given key press or release like a [`WindowEvent::KeyboardInput`] event.
+    pub fn handle_key_input(&mut self, input: &KeyInput) -> EventResponse {
+        let owner = self.capture.key_owner(&self.context, input.physical_key);
+        self.capture
+            .update_key(input.physical_key, input.state, owner);
+        self.app_input.update_key(input, Some(owner));
+
+        // Modifier keys also update the modifiers, in case a `ModifiersChanged`
+        // event went missing.
+        let modifiers_changed = self.modifier_keys.update_key(input);
+        if modifiers_changed {
+            self.update_modifier_state();
+        }
+        let events = self.raw_input.events.len();
+
+        let pressed = input.state == winit::event::ElementState::Pressed;
+
+        // Windows reports AltGr as Ctrl+Alt, which is used to type characters
+        // like "@" or "€" on many keyboard layouts.
+        let alt_gr = self.modifier_state.control_key()
+            && self.modifier_state.alt_key()
+            && !self.modifier_state.super_key();
+        let shortcut = self
+            .modifier_state
+            .intersects(ModifiersState::CONTROL | ModifiersState::SUPER);
+
+        if pressed && (!shortcut || alt_gr) {
+            if let Some(ch) = &input.text {
+                let str: String = ch.chars().filter(|c| (self.text_filter)(*c)).collect();
+                if !str.is_empty() {
+                    self.raw_input.events.push(egui::Event::Text(str));
+                }
+            }
+        }
+        let action = if pressed {
+            self.keymap
+                .action(&input.logical_key, input.physical_key, self.modifier_state)
+                .cloned()
+        } else {
+            None
+        };
+
+        // Copy, cut and paste are send to egui in addition to the key, like egui
+        // expects it. Other actions replace the key press and its release.
+        let send_key = match &action {
+            Some(KeyAction::Copy | KeyAction::Cut | KeyAction::Paste) => true,
+            Some(_) => {
+                self.replaced_keys.insert(input.physical_key);
+                false
+            }
+            None => pressed || !self.replaced_keys.remove(&input.physical_key),
+        };
+
+        if send_key {
+            if let Some(key) = winit_to_egui_key_code(&input.logical_key) {
+                self.raw_input.events.push(egui::Event::Key {
+                    key,
+                    physical_key: winit_to_egui_physical_key(input.physical_key),
+                    pressed,
+                    modifiers: winit_to_egui_modifiers(self.modifier_state),
+                    repeat: input.repeat,
+                });
+            }
+        }
+        // User actions are handled by the application in the next frame.
+        let triggered = action.is_some();
+        if let Some(action) = action {
+            self.handle_key_action(action);
+        }
+
+        EventResponse {
+            owner: Some(owner),
+            repaint: modifiers_changed || triggered || self.raw_input.events.len() > events,
+        }
+    }
+
+    /// Updates the modifiers from the held modifier keys.
+    fn update_modifier_state(&mut self) {
+        self.modifier_state = self.modifier_keys.keys().state();
+        self.raw_input.modifiers = winit_to_egui_modifiers(self.modifier_state);
+    }
+
+    /// Sends the egui events of the action triggered by a keyboard shortcut.
+    fn handle_key_action(&mut self, action: KeyAction) {
+        let command = egui::Modifiers {
+            mac_cmd: cfg!(target_os = "macos"),
+            ctrl: !cfg!(target_os = "macos"),
+            command: true,
+            ..Default::default()
+        };
+        let key = match action {
+            KeyAction::Copy => {
+                self.raw_input.events.push(egui::Event::Copy);
+                return;
+            }
+            KeyAction::Cut => {
+                self.raw_input.events.push(egui::Event::Cut);
+                return;
+            }
+            KeyAction::Paste => {
+                #[cfg(feature = "clipboard")]
+                if let Some(ref mut clipboard) = self.clipboard {
+                    if let Ok(contents) = clipboard.get_contents() {
+                        self.raw_input.events.push(egui::Event::Paste(contents))
                     }
                 }
-                if let Some(key) = winit_to_egui_key_code(key) {
-                    match (pressed, ctrl, key) {
-                        (true, true, Key::C) => self.raw_input.events.push(egui::Event::Copy),
-                        (true, true, Key::X) => self.raw_input.events.push(egui::Event::Cut),
-                        (true, true, Key::V) =>
-                        {
-                            #[cfg(feature = "clipboard")]
-                            if let Some(ref mut clipboard) = self.clipboard {
-                                if let Ok(contents) = clipboard.get_contents() {
-                                    self.raw_input.events.push(egui::Event::Text(contents))
-                                }
-                            }
-                        }
-                        _ => {
-                            self.raw_input.events.push(egui::Event::Key {
-                                key,
-                                physical_key: None,
-                                pressed,
-                                modifiers: winit_to_egui_modifiers(self.modifier_state),
-                                repeat: false,
+                return;
+            }
+            KeyAction::User(name) => {
+                self.triggered_actions.push(name);
+                return;
+            }
+            KeyAction::Undo => (Key::Z, command),
+            KeyAction::Redo => (Key::Z, command | egui::Modifiers::SHIFT),
+            KeyAction::SelectAll => (Key::A, command),
+        };
+
+        // Egui reacts to these shortcuts itself, so the keys are send in place of the
+        // ones that were actually pressed.
+        for pressed in [true, false] {
+            self.raw_input.events.push(egui::Event::Key {
+                key: key.0,
+                physical_key: None,
+                pressed,
+                modifiers: key.1,
+                repeat: false,
+            });
+        }
+    }
+
+    /// Returns the egui device ID for the given winit device ID.
+    fn touch_device_index(&mut self, device_id: winit::event::DeviceId) -> u64 {
+        match self.device_indices.get(&device_id) {
+            Some(id) => *id,
+            None => {
+                let index = self.next_device_index;
+                self.device_indices.insert(device_id, index);
+                self.next_device_index += 1;
+                index
+            }
+        }
+    }
+
+    /// Forwards the touch event to egui and emulates pointer events from it.
+    fn handle_touch(&mut self, touch: &winit::event::Touch) {
+        let pointer_pos = pos2(
+            touch.location.x as f32 / self.scale_factor as f32,
+            touch.location.y as f32 / self.scale_factor as f32,
+        );
+
+        let device_id = self.touch_device_index(touch.device_id);
+        let egui_phase = match touch.phase {
+            TouchPhase::Started => egui::TouchPhase::Start,
+            TouchPhase::Moved => egui::TouchPhase::Move,
+            TouchPhase::Ended => egui::TouchPhase::End,
+            TouchPhase::Cancelled => egui::TouchPhase::Cancel,
+        };
+
+        let force = match touch.force {
+            Some(winit::event::Force::Calibrated { force, .. }) => Some(force as f32),
+            Some(winit::event::Force::Normalized(force)) => Some(force as f32),
+            None => None,
+        };
+
+        self.raw_input.events.push(egui::Event::Touch {
+            device_id: egui::TouchDeviceId(device_id),
+            id: egui::TouchId(touch.id),
+            phase: egui_phase,
+            pos: pointer_pos,
+            force,
+        });
+
+        let scrolling = match self.touch_gestures.as_mut() {
+            Some(gestures) => gestures.handle_touch(
+                (device_id, touch.id),
+                touch.phase,
+                pointer_pos,
+                self.raw_input.time,
+                self.raw_input.modifiers,
+                &mut self.raw_input.events,
+            ),
+            None => false,
+        };
+
+        // Currently Winit doesn't emulate pointer events based on
+        // touch events but Egui requires pointer emulation.
+        let (was_pressed, is_pressed) = match self.touch_pointer_emulation {
+            TouchPointerEmulation::Merged => {
+                // We merge all touch pointers into a single virtual pointer and
+                // ref-count the press state (i.e. the pointer will remain pressed
+                // during multi-touch events until the last pointer is lifted up)
+                let was_pressed = self.touch_pointer_pressed > 0;
+                match touch.phase {
+                    TouchPhase::Started => {
+                        self.touch_pointer_pressed += 1;
+                    }
+                    TouchPhase::Ended | TouchPhase::Cancelled => {
+                        self.touch_pointer_pressed = self
+                            .touch_pointer_pressed
+                            .checked_sub(1)
+                            .unwrap_or_else(|| {
+                                eprintln!("Pointer emulation error: Unbalanced touch start/stop events from Winit");
+                                0
                             });
-                        }
                     }
+                    TouchPhase::Moved => {}
+                }
+                (was_pressed, self.touch_pointer_pressed > 0)
+            }
+            TouchPointerEmulation::FirstFinger => {
+                let finger = Some((device_id, touch.id));
+                let was_pressed = self.touch_pointer_finger.is_some();
+                if was_pressed && self.touch_pointer_finger != finger {
+                    // Only the first finger emulates the pointer.
+                    return;
+                }
+                match touch.phase {
+                    TouchPhase::Started => self.touch_pointer_finger = finger,
+                    TouchPhase::Ended | TouchPhase::Cancelled => self.touch_pointer_finger = None,
+                    TouchPhase::Moved => {}
                 }
+                (was_pressed, self.touch_pointer_finger.is_some())
             }
-            _ => {}
+            TouchPointerEmulation::Disabled => return,
+        };
+
+        if !was_pressed && is_pressed {
+            self.pointer_pos = Some(pointer_pos);
+            self.last_pointer_pos = Some(pointer_pos);
+            self.raw_input
+                .events
+                .push(egui::Event::PointerMoved(pointer_pos));
+            self.raw_input.events.push(egui::Event::PointerButton {
+                pos: pointer_pos,
+                button: egui::PointerButton::Primary,
+                pressed: true,
+                modifiers: Default::default(),
+            });
+        } else if was_pressed && !is_pressed {
+            // Egui docs say that the pressed=false should be sent _before_
+            // the PointerGone.
+            self.raw_input.events.push(egui::Event::PointerButton {
+                pos: pointer_pos,
+                button: egui::PointerButton::Primary,
+                pressed: false,
+                modifiers: Default::default(),
+            });
+            self.raw_input.events.push(egui::Event::PointerGone);
+            self.pointer_pos = None;
+        } else if is_pressed && touch.phase == TouchPhase::Moved && !scrolling {
+            self.pointer_pos = Some(pointer_pos);
+            self.last_pointer_pos = Some(pointer_pos);
+            self.raw_input
+                .events
+                .push(egui::Event::PointerMoved(pointer_pos));
         }
     }
 
     /// Returns `true` if egui should handle the event exclusively. Check this to
     /// avoid unexpected interactions, e.g. a mouse click registering "behind" the UI.
+    ///
+    /// This respects the ownership of pressed buttons, fingers and keys the same way as
+    /// the [`EventResponse`] returned by [`Self::handle_event`].
     pub fn captures_event(&self, event: &WindowEvent) -> bool {
-        match event {
-            KeyboardInput { .. } | ModifiersChanged(_) => self.context().wants_keyboard_input(),
+        let finger = match event {
+            Touch(touch) => self
+                .device_indices
+                .get(&touch.device_id)
+                .map(|index| (*index, touch.id)),
+            _ => None,
+        };
+        self.capture
+            .owner(&self.context, event, finger, self.scale_factor)
+            == Some(InputOwner::Egui)
+    }
 
-            MouseWheel { .. } | MouseInput { .. } => self.context().wants_pointer_input(),
+    /// Returns the keyboard shortcuts.
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
 
-            CursorMoved { .. } => self.context().is_using_pointer(),
+    /// Returns the keyboard shortcuts for modification.
+    pub fn keymap_mut(&mut self) -> &mut Keymap {
+        &mut self.keymap
+    }
 
-            Touch { .. } => self.context().is_using_pointer(),
+    /// Replaces the clipboard of the system, e.g. with one that keeps the text in memory for
+    /// tests. `None` disables copying and pasting.
+    #[cfg(feature = "clipboard")]
+    pub fn set_clipboard(&mut self, clipboard: Option<Box<dyn ClipboardProvider>>) {
+        self.clipboard = clipboard;
+    }
 
-            _ => false,
-        }
+    /// Sets the filter that decides which characters of the text input are send to egui.
+    pub fn set_text_filter(&mut self, text_filter: fn(char) -> bool) {
+        self.text_filter = text_filter;
+    }
+
+    /// Returns the names of the [`KeyAction::User`] actions that were triggered by keyboard
+    /// shortcuts since the last call, in the order they were triggered.
+    pub fn take_triggered_actions(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.triggered_actions)
+    }
+
+    /// Returns the keyboard and mouse state of the application, without the input egui
+    /// consumed. It's updated by [`Self::handle_event`] and the scrolling is collected
+    /// per frame by [`Self::begin_pass`].
+    pub fn app_input(&self) -> &AppInputState {
+        &self.app_input
+    }
+
+    /// Returns the held modifier keys, separated by the side of the keyboard.
+    pub fn modifier_keys(&self) -> ModifierKeys {
+        self.modifier_keys.keys()
     }
 
     /// Updates the internal time for egui used for animations. `elapsed_seconds` should be the seconds since some point in time (for example application start).
@@ -349,27 +858,85 @@ impl Platform {
 
     /// Starts a new frame by providing a new `Ui` instance to write into.
     pub fn begin_pass(&mut self) {
+        let queued: Vec<_> = input_sender::lock(&self.input_queue).drain().collect();
+        for input in queued {
+            match input {
+                input_sender::QueuedInput::Event(event) => self.raw_input.events.push(event),
+                input_sender::QueuedInput::Key(input) => {
+                    self.handle_key_input(&input);
+                }
+                input_sender::QueuedInput::DroppedFile(file) => {
+                    self.raw_input.dropped_files.push(file)
+                }
+            }
+        }
+
+        let raw_input = &mut self.raw_input;
+        let long_press_due = self.touch_gestures.as_mut().and_then(|gestures| {
+            gestures.update(raw_input.time, raw_input.modifiers, &mut raw_input.events)
+        });
+
         self.context.begin_pass(self.raw_input.take());
+        self.app_input.begin_pass();
+
+        if let Some(seconds) = long_press_due {
+            self.context.request_repaint_after_secs(seconds as f32);
+        }
     }
 
     /// Ends the frame. Returns what has happened as `Output` and gives you the draw instructions
-    /// as `PaintJobs`. If the optional `window` is set, it will set the cursor key based on
-    /// egui's instructions.
+    /// as `PaintJobs`. If the optional `window` is set, it will set the cursor key, the cursor
+    /// visibility and the cursor grab based on egui's instructions.
     pub fn end_pass(&mut self, window: Option<&winit::window::Window>) -> egui::FullOutput {
         // otherwise the below line gets flagged by clippy if both clipboard and webbrowser features are disabled
         #[allow(clippy::let_and_return)]
         let output = self.context.end_pass();
 
-        if let Some(window) = window {
-            if let Some(cursor_icon) = egui_to_winit_cursor_icon(output.platform_output.cursor_icon)
-            {
-                window.set_cursor_visible(true);
-                // if the pointer is located inside the window, set cursor icon
-                if self.pointer_pos.is_some() {
-                    window.set_cursor(cursor_icon);
+        if let Some(viewport) = output.viewport_output.get(&egui::ViewportId::ROOT) {
+            for command in &viewport.commands {
+                match command {
+                    egui::ViewportCommand::Screenshot(user_data) => {
+                        self.screenshot_requests.push(user_data.clone());
+                    }
+                    command => self.cursor.command(command),
                 }
-            } else {
-                window.set_cursor_visible(false);
+            }
+        }
+
+        if let Some(window) = window {
+            let update = self.cursor.update(
+                output.platform_output.cursor_icon,
+                self.pointer_pos.is_some(),
+            );
+            cursor::apply(window, update);
+        }
+
+        if let Some(damage) = self.damage.as_mut() {
+            self.damage_regions = damage.update(
+                &output.shapes,
+                &output.textures_delta,
+                frame::screen_size_in_pixels(&self.context, output.pixels_per_point),
+                output.pixels_per_point,
+            );
+        }
+
+        #[cfg(feature = "accesskit")]
+        if let (Some(adapter), Some(update)) = (
+            self.accesskit.as_mut(),
+            output.platform_output.accesskit_update.as_ref(),
+        ) {
+            adapter.update_if_active(|| update.clone());
+        }
+
+        #[cfg(any(feature = "automation", feature = "testing"))]
+        if let Some(update) = output.platform_output.accesskit_update.as_ref() {
+            self.access_tree.update(update);
+        }
+
+        #[cfg(feature = "persistence")]
+        if self.storage.as_ref().is_some_and(Storage::autosave_due) {
+            if let Err(err) = self.save() {
+                eprintln!("Can't save storage: {}", err);
             }
         }
 
@@ -382,6 +949,368 @@ impl Platform {
         output
     }
 
+    /// Makes [`Context::request_repaint`] wake the event loop by sending a [`RepaintEvent`]
+    /// through the proxy, also when it's called from another thread. Pass the received events
+    /// to [`Self::handle_repaint_event`].
+    pub fn set_repaint_proxy<T>(&mut self, proxy: winit::event_loop::EventLoopProxy<T>)
+    where
+        T: From<RepaintEvent> + Send + 'static,
+    {
+        repaint::install_proxy(&self.context, proxy);
+    }
+
+    /// Handles a [`RepaintEvent`] sent by the proxy of [`Self::set_repaint_proxy`]. Requests a
+    /// redraw of the window if the repaint is due, otherwise returns the time at which it's
+    /// due, which should be passed to [`winit::event_loop::ControlFlow::WaitUntil`]. Events of
+    /// passes that were already followed by a repaint are ignored.
+    pub fn handle_repaint_event(
+        &self,
+        event: &RepaintEvent,
+        window: &winit::window::Window,
+    ) -> Option<std::time::Instant> {
+        let pass_nr = self.context.cumulative_pass_nr();
+        match repaint::schedule(event, pass_nr, std::time::Instant::now()) {
+            repaint::Repaint::Ignore => None,
+            repaint::Repaint::Now => {
+                window.request_redraw();
+                None
+            }
+            repaint::Repaint::At(when) => Some(when),
+        }
+    }
+
+    /// Returns a sender that queues input from other threads for the next frame.
+    pub fn input_sender(&self) -> InputSender {
+        InputSender::new(self.input_queue.clone(), self.context.clone())
+    }
+
+    /// Sets how many inputs the [`InputSender`]s queue at most between two frames. Defaults to
+    /// 1024.
+    pub fn set_input_queue_capacity(&mut self, capacity: usize) {
+        input_sender::lock(&self.input_queue).set_capacity(capacity);
+    }
+
+    /// Starts a server on a local socket that lets scripts drive the UI, e.g. for end-to-end
+    /// tests. Returns the address the server listens on. Requests are answered by
+    /// [`Self::process_automation`], which should be called regularly. The server and its
+    /// connections are closed when the platform is dropped or another server is started.
+    ///
+    /// Clients send one JSON object per line and receive one response per line. Lines longer
+    /// than 1 MiB are refused. Every request has a `"command"` and an optional `"id"`, which
+    /// is copied to the response. Responses have `"ok": true` or `"ok": false` and an
+    /// `"error"` message. The commands are:
+    ///
+    /// - `pointer_move` with `x` and `y` in points
+    /// - `pointer_button` with `x`, `y`, `pressed` and optionally `button` (`primary`,
+    ///   `secondary` or `middle`) and `modifiers`
+    /// - `click` with `x`, `y` and optionally `button` and `modifiers`
+    /// - `click_label`, which clicks the center of the widget with the given `label`
+    /// - `key` with a `key` name of [`egui::Key`] and optionally `pressed` and `modifiers`.
+    ///   The key is pressed and released without `pressed`. Keys of characters also input
+    ///   their text.
+    /// - `text` with the `text` to input, which is typed with a key press per character
+    /// - `frames` runs `count` frames, 1 by default and 100 at most, and returns the number
+    ///   of `frames` it ran. Input is only handled by the next frame, so a frame should run
+    ///   after each click.
+    /// - `tree` returns the AccessKit `nodes` with their `id`, `role`, `label`, `value`,
+    ///   `numeric_value`, `rect` in points, `toggled` state, `disabled`, `hidden` and `focused`
+    ///   flags and the IDs of their `children`
+    /// - `screenshot` runs a frame and returns its `width`, `height` and the `rgba` pixels
+    ///   with premultiplied alpha, encoded as base64
+    ///
+    /// The `modifiers` are an array of `alt`, `ctrl`, `shift`, `mac_cmd` and `command`, which
+    /// are held in addition to the held modifiers. The input is handled like the winit events
+    /// of a user, so it's also seen by [`Self::app_input`], the keymap and input capture.
+    #[cfg(feature = "automation")]
+    pub fn start_automation(
+        &mut self,
+        address: &AutomationAddress,
+    ) -> std::io::Result<AutomationAddress> {
+        let (server, bound) = automation::AutomationServer::start(address, self.context.clone())?;
+        self.context.enable_accesskit();
+        self.automation = Some(server);
+        Ok(bound)
+    }
+
+    /// Answers the requests of the automation server. `frame` must run a frame of the
+    /// application, from [`Self::begin_pass`] to painting it, and return the painted image if
+    /// the frame has screenshot requests, see [`Self::take_screenshot_requests`]. Returning
+    /// `None` makes screenshots fail.
+    #[cfg(feature = "automation")]
+    pub fn process_automation(
+        &mut self,
+        mut frame: impl FnMut(&mut Platform) -> Option<egui::ColorImage>,
+    ) {
+        automation::process(self, &mut frame);
+    }
+
+    /// Runs a frame with the given UI without painting it.
+    #[cfg(feature = "testing")]
+    pub fn run_frame(&mut self, ui: impl FnOnce(&Context)) -> egui::FullOutput {
+        self.begin_pass();
+        ui(&self.context);
+        self.end_pass(None)
+    }
+
+    /// Runs frames with the given UI until egui doesn't request another frame right away, so
+    /// that the input was handled and animations are done. Enables the AccessKit tree that
+    /// the widgets are found in. Returns the number of frames, which are at most 100.
+    #[cfg(feature = "testing")]
+    pub fn run_until_settled(&mut self, mut ui: impl FnMut(&Context)) -> usize {
+        self.context.enable_accesskit();
+        for frame in 1..=testing::MAX_SETTLE_FRAMES {
+            let output = self.run_frame(&mut ui);
+            let settled = output
+                .viewport_output
+                .get(&egui::ViewportId::ROOT)
+                .is_none_or(|viewport| !viewport.repaint_delay.is_zero());
+            if settled {
+                return frame;
+            }
+        }
+        testing::MAX_SETTLE_FRAMES
+    }
+
+    /// Returns all widgets of the last frame in depth-first order.
+    #[cfg(feature = "testing")]
+    pub fn widgets(&self) -> Vec<WidgetInfo> {
+        self.access_tree
+            .walk()
+            .into_iter()
+            .map(|(id, node)| WidgetInfo::new(&self.access_tree, id, node))
+            .collect()
+    }
+
+    /// Returns the first visible widget of the last frame with the given label, in
+    /// depth-first order. A widget that is labelled by a label, like a text edit, is preferred
+    /// over the label itself. See [`Self::run_until_settled`].
+    #[cfg(feature = "testing")]
+    pub fn query_by_label(&mut self, label: &str) -> Option<Widget<'_>> {
+        let info = self
+            .access_tree
+            .find_label(label)
+            .first()
+            .map(|(id, node)| WidgetInfo::new(&self.access_tree, *id, node))?;
+        Some(Widget::new(self, info))
+    }
+
+    /// Returns the widget like [`Self::query_by_label`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no widget with the label.
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn find_by_label(&mut self, label: &str) -> Widget<'_> {
+        if self.access_tree.find_label(label).is_empty() {
+            let labels: Vec<String> = self
+                .widgets()
+                .into_iter()
+                .filter_map(|widget| widget.label)
+                .collect();
+            panic!(
+                "No widget with the label {:?}, the labels are {:?}",
+                label, labels
+            );
+        }
+        self.query_by_label(label).unwrap()
+    }
+
+    /// Asserts that there's no visible widget with the given label.
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn assert_no_label(&self, label: &str) {
+        assert!(
+            self.access_tree.find_label(label).is_empty(),
+            "Found a widget with the label {:?}",
+            label
+        );
+    }
+
+    /// Types the text into the focused widget, by pressing and releasing a key for each
+    /// character.
+    #[cfg(feature = "testing")]
+    pub fn type_text(&mut self, text: &str) {
+        let mut buffer = [0; 4];
+        for c in text.chars() {
+            let key = winit::keyboard::Key::Character(c.encode_utf8(&mut buffer).into());
+            self.press_key(key);
+        }
+    }
+
+    /// Presses and releases the key, e.g. `Key::Named(NamedKey::Enter)`.
+    #[cfg(feature = "testing")]
+    pub fn press_key(&mut self, key: winit::keyboard::Key) {
+        for input in &keyboard::key_presses(key) {
+            self.handle_key_input(input);
+        }
+    }
+
+    /// Sets the storage that is used to persist the egui memory and application data. The egui
+    /// memory is restored from the storage, so this should be called before the first frame.
+    #[cfg(feature = "persistence")]
+    pub fn set_storage(&mut self, storage: Storage) {
+        if let Some(memory) = storage.get_value(persistence::EGUI_MEMORY_KEY) {
+            self.context.memory_mut(|m| *m = memory);
+        }
+        self.storage = Some(storage);
+    }
+
+    /// Returns the storage.
+    #[cfg(feature = "persistence")]
+    pub fn storage(&self) -> Option<&Storage> {
+        self.storage.as_ref()
+    }
+
+    /// Returns the storage for modification, e.g. to store application data.
+    #[cfg(feature = "persistence")]
+    pub fn storage_mut(&mut self) -> Option<&mut Storage> {
+        self.storage.as_mut()
+    }
+
+    /// Stores the egui memory and the window geometry and writes the storage to disk. This
+    /// happens automatically in [`Self::end_pass`] and on [`WindowEvent::CloseRequested`] and
+    /// [`WindowEvent::Destroyed`].
+    #[cfg(feature = "persistence")]
+    pub fn save(&mut self) -> std::io::Result<()> {
+        if let Some(storage) = self.storage.as_mut() {
+            self.context
+                .memory(|m| storage.set_value(persistence::EGUI_MEMORY_KEY, m));
+            storage.set_value(persistence::WINDOW_GEOMETRY_KEY, &self.window_geometry);
+            storage.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Captures the geometry of the window, including the monitor and the maximized and
+    /// fullscreen state, which aren't reported by window events. Should be called when the
+    /// window is created and after its state was changed.
+    pub fn capture_window_geometry(&mut self, window: &winit::window::Window) {
+        self.window_geometry = WindowGeometry::from_window(window);
+    }
+
+    /// Returns the geometry of the native window, which is updated by [`WindowEvent::Resized`],
+    /// [`WindowEvent::Moved`] and [`WindowEvent::ScaleFactorChanged`] events and by
+    /// [`Self::capture_window_geometry`]. Use [`WindowGeometry::window_attributes`] to restore it.
+    pub fn window_geometry(&self) -> &WindowGeometry {
+        &self.window_geometry
+    }
+
+    /// Returns the screenshots egui requested with [`egui::ViewportCommand::Screenshot`] since
+    /// the last call. The renderer should capture the frame after painting it and pass the
+    /// image to [`Self::deliver_screenshot`] once per request.
+    pub fn take_screenshot_requests(&mut self) -> Vec<egui::UserData> {
+        std::mem::take(&mut self.screenshot_requests)
+    }
+
+    /// Sends the screenshot to egui as [`egui::Event::Screenshot`] in the next frame, which is
+    /// requested to be painted.
+    pub fn deliver_screenshot(&mut self, image: egui::ColorImage, user_data: egui::UserData) {
+        self.raw_input.events.push(egui::Event::Screenshot {
+            viewport_id: egui::ViewportId::ROOT,
+            user_data,
+            image: std::sync::Arc::new(image),
+        });
+        self.context.request_repaint();
+    }
+
+    /// Returns the regions in physical pixel that changed in the last frame. Everything outside
+    /// of them looks the same as in the frame before, so a renderer only has to redraw these
+    /// regions, e.g. with scissor rectangles or partial presentation. The first frame and
+    /// frames with a new screen size or scale damage the whole screen. An empty slice means
+    /// nothing changed.
+    ///
+    /// Returns `None` if [`PlatformDescriptor::damage_tracking`] is disabled.
+    pub fn damage_regions(&self) -> Option<&[egui::Rect]> {
+        self.damage.as_ref().map(|_| self.damage_regions.as_slice())
+    }
+
+    /// Ends the frame like [`Self::end_pass`] and tessellates the shapes at the
+    /// `pixels_per_point` of the frame.
+    pub fn end_pass_tessellated(&mut self, window: Option<&winit::window::Window>) -> FrameOutput {
+        let output = self.end_pass(window);
+        let screenshot_requests = self.take_screenshot_requests();
+        let (mut frame, shapes) = FrameOutput::new(&self.context, output, screenshot_requests);
+        frame.primitives = self.context.tessellate(shapes, frame.pixels_per_point);
+        frame
+    }
+
+    /// Ends the frame like [`Self::end_pass_tessellated`], but tessellates the shapes on a
+    /// worker thread. The worker doesn't use egui's context, so input can be handled with
+    /// [`Self::handle_event`] while the tessellation runs.
+    pub fn end_pass_tessellate_async(
+        &mut self,
+        window: Option<&winit::window::Window>,
+    ) -> PendingFrame {
+        let output = self.end_pass(window);
+        let screenshot_requests = self.take_screenshot_requests();
+        PendingFrame::spawn(
+            &self.context,
+            output,
+            screenshot_requests,
+            &mut self.disc_atlas,
+        )
+    }
+
+    /// Registers a custom cursor created from the given image under the given `id`. The
+    /// `hotspot` is the position of the click point inside the image in pixel. Registering
+    /// a cursor with an already used `id` replaces the old cursor.
+    ///
+    /// Use [`Self::set_cursor_override`] to show the cursor in place of an egui cursor icon.
+    pub fn register_custom_cursor(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        id: impl Into<String>,
+        image: &egui::ColorImage,
+        hotspot: [u16; 2],
+    ) -> Result<(), winit::window::BadImage> {
+        let [width, height] = image.size;
+        let (width, height) = match (u16::try_from(width), u16::try_from(height)) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => {
+                return Err(winit::window::BadImage::TooLarge {
+                    width: width.min(u16::MAX as usize) as u16,
+                    height: height.min(u16::MAX as usize) as u16,
+                })
+            }
+        };
+        let rgba: Vec<u8> = image
+            .pixels
+            .iter()
+            .flat_map(|color| color.to_srgba_unmultiplied())
+            .collect();
+
+        let source = CustomCursor::from_rgba(rgba, width, height, hotspot[0], hotspot[1])?;
+        self.cursor
+            .register_custom_cursor(id.into(), event_loop.create_custom_cursor(source));
+
+        Ok(())
+    }
+
+    /// Removes the custom cursor with the given `id`. Egui cursor icons that were overridden
+    /// with it fall back to the default winit cursor icons.
+    pub fn remove_custom_cursor(&mut self, id: &str) {
+        self.cursor.remove_custom_cursor(id);
+    }
+
+    /// Shows the custom cursor with the given `id` whenever egui requests the given cursor
+    /// `icon`. Passing `None` as `id` restores the default mapping of the `icon`.
+    pub fn set_cursor_override(&mut self, icon: egui::CursorIcon, id: Option<&str>) {
+        self.cursor.set_override(icon, id);
+    }
+
+    /// Forces the cursor icon, visibility and grab to be applied to the window again with the
+    /// next [`Self::end_pass`], even if they didn't change. This is done automatically when
+    /// the window regains focus.
+    pub fn reapply_cursor(&mut self) {
+        self.cursor.reapply();
+    }
+
+    /// Returns the cursor grab mode that was last requested by egui.
+    pub fn cursor_grab(&self) -> egui::viewport::CursorGrab {
+        self.cursor.grab()
+    }
+
     /// Returns the internal egui context.
     pub fn context(&self) -> Context {
         self.context.clone()
@@ -394,50 +1323,250 @@ impl Platform {
     }
 }
 
+/// The named winit keys that have an egui key.
+const NAMED_KEYS: [(NamedKey, Key); 54] = [
+    (NamedKey::Escape, Key::Escape),
+    (NamedKey::Insert, Key::Insert),
+    (NamedKey::Home, Key::Home),
+    (NamedKey::Delete, Key::Delete),
+    (NamedKey::End, Key::End),
+    (NamedKey::PageDown, Key::PageDown),
+    (NamedKey::PageUp, Key::PageUp),
+    (NamedKey::ArrowLeft, Key::ArrowLeft),
+    (NamedKey::ArrowUp, Key::ArrowUp),
+    (NamedKey::ArrowRight, Key::ArrowRight),
+    (NamedKey::ArrowDown, Key::ArrowDown),
+    (NamedKey::Backspace, Key::Backspace),
+    (NamedKey::Enter, Key::Enter),
+    (NamedKey::Tab, Key::Tab),
+    (NamedKey::Space, Key::Space),
+    (NamedKey::F1, Key::F1),
+    (NamedKey::F2, Key::F2),
+    (NamedKey::F3, Key::F3),
+    (NamedKey::F4, Key::F4),
+    (NamedKey::F5, Key::F5),
+    (NamedKey::F6, Key::F6),
+    (NamedKey::F7, Key::F7),
+    (NamedKey::F8, Key::F8),
+    (NamedKey::F9, Key::F9),
+    (NamedKey::F10, Key::F10),
+    (NamedKey::F11, Key::F11),
+    (NamedKey::F12, Key::F12),
+    (NamedKey::F13, Key::F13),
+    (NamedKey::F14, Key::F14),
+    (NamedKey::F15, Key::F15),
+    (NamedKey::F16, Key::F16),
+    (NamedKey::F17, Key::F17),
+    (NamedKey::F18, Key::F18),
+    (NamedKey::F19, Key::F19),
+    (NamedKey::F20, Key::F20),
+    (NamedKey::F21, Key::F21),
+    (NamedKey::F22, Key::F22),
+    (NamedKey::F23, Key::F23),
+    (NamedKey::F24, Key::F24),
+    (NamedKey::F25, Key::F25),
+    (NamedKey::F26, Key::F26),
+    (NamedKey::F27, Key::F27),
+    (NamedKey::F28, Key::F28),
+    (NamedKey::F29, Key::F29),
+    (NamedKey::F30, Key::F30),
+    (NamedKey::F31, Key::F31),
+    (NamedKey::F32, Key::F32),
+    (NamedKey::F33, Key::F33),
+    (NamedKey::F34, Key::F34),
+    (NamedKey::F35, Key::F35),
+    (NamedKey::Copy, Key::Copy),
+    (NamedKey::Cut, Key::Cut),
+    (NamedKey::Paste, Key::Paste),
+    (NamedKey::BrowserBack, Key::BrowserBack),
+];
+
 /// Translates winit to egui keycodes.
 #[inline]
 fn winit_to_egui_key_code(key: &winit::keyboard::Key) -> Option<Key> {
-    Some(match key {
-        winit::keyboard::Key::Named(NamedKey::Escape) => Key::Escape,
-        winit::keyboard::Key::Named(NamedKey::Insert) => Key::Insert,
-        winit::keyboard::Key::Named(NamedKey::Home) => Key::Home,
-        winit::keyboard::Key::Named(NamedKey::Delete) => Key::Delete,
-        winit::keyboard::Key::Named(NamedKey::End) => Key::End,
-        winit::keyboard::Key::Named(NamedKey::PageDown) => Key::PageDown,
-        winit::keyboard::Key::Named(NamedKey::PageUp) => Key::PageUp,
-        winit::keyboard::Key::Named(NamedKey::ArrowLeft) => Key::ArrowLeft,
-        winit::keyboard::Key::Named(NamedKey::ArrowUp) => Key::ArrowUp,
-        winit::keyboard::Key::Named(NamedKey::ArrowRight) => Key::ArrowRight,
-        winit::keyboard::Key::Named(NamedKey::ArrowDown) => Key::ArrowDown,
-        winit::keyboard::Key::Named(NamedKey::Backspace) => Key::Backspace,
-        winit::keyboard::Key::Named(NamedKey::Enter) => Key::Enter,
-        winit::keyboard::Key::Named(NamedKey::Tab) => Key::Tab,
-        winit::keyboard::Key::Named(NamedKey::Space) => Key::Space,
-        winit::keyboard::Key::Named(NamedKey::F1) => Key::F1,
-        winit::keyboard::Key::Named(NamedKey::F2) => Key::F2,
-        winit::keyboard::Key::Named(NamedKey::F3) => Key::F3,
-        winit::keyboard::Key::Named(NamedKey::F4) => Key::F4,
-        winit::keyboard::Key::Named(NamedKey::F5) => Key::F5,
-        winit::keyboard::Key::Named(NamedKey::F6) => Key::F6,
-        winit::keyboard::Key::Named(NamedKey::F7) => Key::F7,
-        winit::keyboard::Key::Named(NamedKey::F8) => Key::F8,
-        winit::keyboard::Key::Named(NamedKey::F9) => Key::F9,
-        winit::keyboard::Key::Named(NamedKey::F10) => Key::F10,
-        winit::keyboard::Key::Named(NamedKey::F11) => Key::F11,
-        winit::keyboard::Key::Named(NamedKey::F12) => Key::F12,
-        winit::keyboard::Key::Named(NamedKey::F13) => Key::F13,
-        winit::keyboard::Key::Named(NamedKey::F14) => Key::F14,
-        winit::keyboard::Key::Named(NamedKey::F15) => Key::F15,
-        winit::keyboard::Key::Named(NamedKey::F16) => Key::F16,
-        winit::keyboard::Key::Named(NamedKey::F17) => Key::F17,
-        winit::keyboard::Key::Named(NamedKey::F18) => Key::F18,
-        winit::keyboard::Key::Named(NamedKey::F19) => Key::F19,
-        winit::keyboard::Key::Named(NamedKey::F20) => Key::F20,
-        winit::keyboard::Key::Character(c) => Key::from_name(c)?,
-        _ => {
-            return None;
+    match key {
+        winit::keyboard::Key::Named(named) => NAMED_KEYS
+            .iter()
+            .find(|(winit_key, _)| winit_key == named)
+            .map(|(_, key)| *key),
+        winit::keyboard::Key::Character(c) => Key::from_name(c),
+        _ => None,
+    }
+}
+
+/// Translates egui to winit keys, the reverse of [`winit_to_egui_key_code`].
+#[cfg(feature = "automation")]
+fn egui_to_winit_key(key: Key) -> winit::keyboard::Key {
+    match NAMED_KEYS.iter().find(|(_, egui_key)| *egui_key == key) {
+        Some((named, _)) => winit::keyboard::Key::Named(*named),
+        None => {
+            let name = key.symbol_or_name();
+            // Keys of letters produce lowercase characters without shift.
+            if name.chars().count() == 1 {
+                winit::keyboard::Key::Character(name.to_lowercase().into())
+            } else {
+                winit::keyboard::Key::Unidentified(winit::keyboard::NativeKey::Unidentified)
+            }
         }
-    })
+    }
+}
+
+/// Physical winit keys and the egui keys they are named after on a US keyboard layout.
+const PHYSICAL_KEYS: [(KeyCode, Key); 102] = [
+    (KeyCode::ArrowDown, Key::ArrowDown),
+    (KeyCode::ArrowLeft, Key::ArrowLeft),
+    (KeyCode::ArrowRight, Key::ArrowRight),
+    (KeyCode::ArrowUp, Key::ArrowUp),
+    (KeyCode::Escape, Key::Escape),
+    (KeyCode::Tab, Key::Tab),
+    (KeyCode::Backspace, Key::Backspace),
+    (KeyCode::Enter, Key::Enter),
+    (KeyCode::Space, Key::Space),
+    (KeyCode::Insert, Key::Insert),
+    (KeyCode::Delete, Key::Delete),
+    (KeyCode::Home, Key::Home),
+    (KeyCode::End, Key::End),
+    (KeyCode::PageUp, Key::PageUp),
+    (KeyCode::PageDown, Key::PageDown),
+    (KeyCode::Copy, Key::Copy),
+    (KeyCode::Cut, Key::Cut),
+    (KeyCode::Paste, Key::Paste),
+    (KeyCode::Comma, Key::Comma),
+    (KeyCode::Backslash, Key::Backslash),
+    (KeyCode::Slash, Key::Slash),
+    (KeyCode::BracketLeft, Key::OpenBracket),
+    (KeyCode::BracketRight, Key::CloseBracket),
+    (KeyCode::Backquote, Key::Backtick),
+    (KeyCode::Minus, Key::Minus),
+    (KeyCode::Period, Key::Period),
+    (KeyCode::NumpadAdd, Key::Plus),
+    (KeyCode::Equal, Key::Equals),
+    (KeyCode::Semicolon, Key::Semicolon),
+    (KeyCode::Quote, Key::Quote),
+    (KeyCode::Digit0, Key::Num0),
+    (KeyCode::Digit1, Key::Num1),
+    (KeyCode::Digit2, Key::Num2),
+    (KeyCode::Digit3, Key::Num3),
+    (KeyCode::Digit4, Key::Num4),
+    (KeyCode::Digit5, Key::Num5),
+    (KeyCode::Digit6, Key::Num6),
+    (KeyCode::Digit7, Key::Num7),
+    (KeyCode::Digit8, Key::Num8),
+    (KeyCode::Digit9, Key::Num9),
+    (KeyCode::KeyA, Key::A),
+    (KeyCode::KeyB, Key::B),
+    (KeyCode::KeyC, Key::C),
+    (KeyCode::KeyD, Key::D),
+    (KeyCode::KeyE, Key::E),
+    (KeyCode::KeyF, Key::F),
+    (KeyCode::KeyG, Key::G),
+    (KeyCode::KeyH, Key::H),
+    (KeyCode::KeyI, Key::I),
+    (KeyCode::KeyJ, Key::J),
+    (KeyCode::KeyK, Key::K),
+    (KeyCode::KeyL, Key::L),
+    (KeyCode::KeyM, Key::M),
+    (KeyCode::KeyN, Key::N),
+    (KeyCode::KeyO, Key::O),
+    (KeyCode::KeyP, Key::P),
+    (KeyCode::KeyQ, Key::Q),
+    (KeyCode::KeyR, Key::R),
+    (KeyCode::KeyS, Key::S),
+    (KeyCode::KeyT, Key::T),
+    (KeyCode::KeyU, Key::U),
+    (KeyCode::KeyV, Key::V),
+    (KeyCode::KeyW, Key::W),
+    (KeyCode::KeyX, Key::X),
+    (KeyCode::KeyY, Key::Y),
+    (KeyCode::KeyZ, Key::Z),
+    (KeyCode::F1, Key::F1),
+    (KeyCode::F2, Key::F2),
+    (KeyCode::F3, Key::F3),
+    (KeyCode::F4, Key::F4),
+    (KeyCode::F5, Key::F5),
+    (KeyCode::F6, Key::F6),
+    (KeyCode::F7, Key::F7),
+    (KeyCode::F8, Key::F8),
+    (KeyCode::F9, Key::F9),
+    (KeyCode::F10, Key::F10),
+    (KeyCode::F11, Key::F11),
+    (KeyCode::F12, Key::F12),
+    (KeyCode::F13, Key::F13),
+    (KeyCode::F14, Key::F14),
+    (KeyCode::F15, Key::F15),
+    (KeyCode::F16, Key::F16),
+    (KeyCode::F17, Key::F17),
+    (KeyCode::F18, Key::F18),
+    (KeyCode::F19, Key::F19),
+    (KeyCode::F20, Key::F20),
+    (KeyCode::F21, Key::F21),
+    (KeyCode::F22, Key::F22),
+    (KeyCode::F23, Key::F23),
+    (KeyCode::F24, Key::F24),
+    (KeyCode::F25, Key::F25),
+    (KeyCode::F26, Key::F26),
+    (KeyCode::F27, Key::F27),
+    (KeyCode::F28, Key::F28),
+    (KeyCode::F29, Key::F29),
+    (KeyCode::F30, Key::F30),
+    (KeyCode::F31, Key::F31),
+    (KeyCode::F32, Key::F32),
+    (KeyCode::F33, Key::F33),
+    (KeyCode::F34, Key::F34),
+    (KeyCode::F35, Key::F35),
+    (KeyCode::BrowserBack, Key::BrowserBack),
+];
+
+/// Translates physical winit keys to egui keys, named after the keys of a US keyboard layout.
+fn winit_to_egui_physical_key(key: winit::keyboard::PhysicalKey) -> Option<Key> {
+    let winit::keyboard::PhysicalKey::Code(code) = key else {
+        return None;
+    };
+
+    // The keys of the numpad are named like the keys of the main block.
+    let code = match code {
+        KeyCode::NumpadEnter => KeyCode::Enter,
+        KeyCode::NumpadComma => KeyCode::Comma,
+        KeyCode::NumpadDivide => KeyCode::Slash,
+        KeyCode::NumpadSubtract => KeyCode::Minus,
+        KeyCode::NumpadDecimal => KeyCode::Period,
+        KeyCode::NumpadEqual => KeyCode::Equal,
+        KeyCode::Numpad0 => KeyCode::Digit0,
+        KeyCode::Numpad1 => KeyCode::Digit1,
+        KeyCode::Numpad2 => KeyCode::Digit2,
+        KeyCode::Numpad3 => KeyCode::Digit3,
+        KeyCode::Numpad4 => KeyCode::Digit4,
+        KeyCode::Numpad5 => KeyCode::Digit5,
+        KeyCode::Numpad6 => KeyCode::Digit6,
+        KeyCode::Numpad7 => KeyCode::Digit7,
+        KeyCode::Numpad8 => KeyCode::Digit8,
+        KeyCode::Numpad9 => KeyCode::Digit9,
+        code => code,
+    };
+    PHYSICAL_KEYS
+        .iter()
+        .find(|(winit_code, _)| *winit_code == code)
+        .map(|(_, key)| *key)
+}
+
+/// Translates egui keys to the physical winit keys they are named after, the reverse of
+/// [`winit_to_egui_physical_key`]. Symbols that are typed with shift are on the key of the
+/// unshifted symbol.
+#[cfg(any(feature = "automation", feature = "testing"))]
+fn egui_to_winit_key_code(key: Key) -> Option<KeyCode> {
+    let key = match key {
+        Key::Colon => Key::Semicolon,
+        Key::Pipe => Key::Backslash,
+        Key::Questionmark => Key::Slash,
+        Key::Exclamationmark => Key::Num1,
+        Key::OpenCurlyBracket => Key::OpenBracket,
+        Key::CloseCurlyBracket => Key::CloseBracket,
+        key => key,
+    };
+    PHYSICAL_KEYS
+        .iter()
+        .find(|(_, egui_key)| *egui_key == key)
+        .map(|(code, _)| *code)
 }
 
 /// Translates winit to egui modifier keys.
@@ -458,6 +1587,8 @@ fn winit_to_egui_modifiers(modifiers: ModifiersState) -> egui::Modifiers {
     }
 }
 
+/// Translates egui to winit cursor icons. Returns `None` for `CursorIcon::None`, which hides
+/// the cursor.
 #[inline]
 fn egui_to_winit_cursor_icon(icon: egui::CursorIcon) -> Option<CursorIcon> {
     use egui::CursorIcon::*;
@@ -0,0 +1,132 @@
+//! Saves the egui memory and application data to disk.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The key under which the egui memory is stored.
+pub(crate) const EGUI_MEMORY_KEY: &str = "egui";
+/// The key under which the geometry of the native window is stored.
+pub(crate) const WINDOW_GEOMETRY_KEY: &str = "window";
+
+/// A key/value storage that is saved as a RON file.
+///
+/// The egui memory is stored under the key `"egui"` and the [`crate::WindowGeometry`] under
+/// the key `"window"`. Once the storage was set with [`crate::Platform::set_storage`], the
+/// platform saves it periodically in [`crate::Platform::end_pass`] and when the window is
+/// closed.
+#[derive(Debug)]
+pub struct Storage {
+    path: PathBuf,
+    values: HashMap<String, String>,
+    dirty: bool,
+    autosave_interval: Duration,
+    last_save: Instant,
+}
+
+impl Storage {
+    /// Loads the storage from the file at the given path. Starts with an empty storage if the
+    /// file doesn't exist or can't be read.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let values = match std::fs::read_to_string(&path) {
+            Ok(text) => match ron::from_str(&text) {
+                Ok(values) => values,
+                Err(err) => {
+                    eprintln!("Can't parse storage {}: {}", path.display(), err);
+                    HashMap::new()
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => {
+                eprintln!("Can't read storage {}: {}", path.display(), err);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path,
+            values,
+            dirty: false,
+            autosave_interval: Duration::from_secs(30),
+            last_save: Instant::now(),
+        }
+    }
+
+    /// Sets how often the storage is saved by [`crate::Platform::end_pass`]. The default is
+    /// 30 seconds.
+    pub fn with_autosave_interval(mut self, interval: Duration) -> Self {
+        self.autosave_interval = interval;
+        self
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the string stored under the given key.
+    pub fn get_string(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Stores the string under the given key.
+    pub fn set_string(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        let value = value.into();
+        if self.values.get(&key) != Some(&value) {
+            self.values.insert(key, value);
+            self.dirty = true;
+        }
+    }
+
+    /// Returns the value stored under the given key. `None` if there is no value or it can't
+    /// be deserialized into `T`.
+    pub fn get_value<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        ron::from_str(self.get_string(key)?).ok()
+    }
+
+    /// Stores the value under the given key.
+    pub fn set_value<T: Serialize>(&mut self, key: impl Into<String>, value: &T) {
+        match ron::to_string(value) {
+            Ok(value) => self.set_string(key, value),
+            Err(err) => eprintln!("Can't serialize storage value: {}", err),
+        }
+    }
+
+    /// Removes the value stored under the given key.
+    pub fn remove(&mut self, key: &str) {
+        if self.values.remove(key).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Writes the storage to disk if it changed since the last save.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.last_save = Instant::now();
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let text = ron::ser::to_string_pretty(&self.values, Default::default())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Written to a temporary file first, so that a crash doesn't leave a truncated file.
+        let temp_path = self.path.with_extension("ron.tmp");
+        std::fs::write(&temp_path, text)?;
+        std::fs::rename(&temp_path, &self.path)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the autosave interval passed since the last save.
+    pub(crate) fn autosave_due(&self) -> bool {
+        self.last_save.elapsed() >= self.autosave_interval
+    }
+}
@@ -0,0 +1,128 @@
+//! Wakes the event loop when egui requests a repaint from another thread.
+
+use std::time::Instant;
+
+use egui::{Context, RequestRepaintInfo, ViewportId};
+use winit::event_loop::EventLoopProxy;
+
+/// The user event that is sent when egui requests a repaint. See
+/// [`crate::Platform::set_repaint_proxy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RepaintEvent {
+    /// The viewport that should be repainted.
+    pub viewport_id: ViewportId,
+    /// The time at which the repaint is due.
+    pub when: Instant,
+    /// The pass during which the repaint was requested, see [`Context::cumulative_pass_nr`].
+    pub cumulative_pass_nr: u64,
+}
+
+/// Installs the repaint callback of the context, which sends a [`RepaintEvent`] through the
+/// proxy.
+pub(crate) fn install_proxy<T>(context: &Context, proxy: EventLoopProxy<T>)
+where
+    T: From<RepaintEvent> + Send + 'static,
+{
+    // The proxy isn't `Sync` on all platforms.
+    let proxy = std::sync::Mutex::new(proxy);
+    context.set_request_repaint_callback(move |info| {
+        let event = match repaint_event(&info, Instant::now()) {
+            Some(event) => event,
+            None => return,
+        };
+        if let Ok(proxy) = proxy.lock() {
+            // Fails if the event loop was closed, in which case there's nothing to repaint.
+            let _ = proxy.send_event(event.into());
+        }
+    });
+}
+
+/// Returns the event of a repaint request at `now`, or `None` if the delay is too long to ever
+/// be due.
+fn repaint_event(info: &RequestRepaintInfo, now: Instant) -> Option<RepaintEvent> {
+    Some(RepaintEvent {
+        viewport_id: info.viewport_id,
+        when: now.checked_add(info.delay)?,
+        cumulative_pass_nr: info.current_cumulative_pass_nr,
+    })
+}
+
+/// What to do about a [`RepaintEvent`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Repaint {
+    /// The event is of another viewport, or a pass already followed the request.
+    Ignore,
+    /// The repaint is due.
+    Now,
+    /// The event loop should wake up at the time to repaint.
+    At(Instant),
+}
+
+/// Decides what to do about the event at `now`, when `cumulative_pass_nr` passes have
+/// completed.
+pub(crate) fn schedule(event: &RepaintEvent, cumulative_pass_nr: u64, now: Instant) -> Repaint {
+    // The pass that requested the repaint may still be running, the one after it is the
+    // requested repaint.
+    if event.viewport_id != ViewportId::ROOT || cumulative_pass_nr > event.cumulative_pass_nr + 1 {
+        Repaint::Ignore
+    } else if event.when <= now {
+        Repaint::Now
+    } else {
+        Repaint::At(event.when)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn request(delay: Duration, pass_nr: u64) -> RequestRepaintInfo {
+        RequestRepaintInfo {
+            viewport_id: ViewportId::ROOT,
+            delay,
+            current_cumulative_pass_nr: pass_nr,
+        }
+    }
+
+    #[test]
+    fn delay_becomes_the_deadline() {
+        let now = Instant::now();
+        let event = repaint_event(&request(Duration::from_millis(250), 3), now).unwrap();
+        assert_eq!(event.when, now + Duration::from_millis(250));
+        assert_eq!(event.cumulative_pass_nr, 3);
+
+        assert_eq!(schedule(&event, 3, now), Repaint::At(event.when));
+        assert_eq!(schedule(&event, 3, event.when), Repaint::Now);
+        assert_eq!(
+            schedule(&event, 3, event.when + Duration::from_secs(1)),
+            Repaint::Now
+        );
+    }
+
+    #[test]
+    fn endless_delay_sends_no_event() {
+        assert_eq!(
+            repaint_event(&request(Duration::MAX, 0), Instant::now()),
+            None
+        );
+    }
+
+    #[test]
+    fn stale_events_are_ignored() {
+        let now = Instant::now();
+        let event = repaint_event(&request(Duration::ZERO, 3), now).unwrap();
+        // Requested during pass 3, which may not have completed yet.
+        assert_eq!(schedule(&event, 3, now), Repaint::Now);
+        assert_eq!(schedule(&event, 4, now), Repaint::Now);
+        // Pass 4 was the requested repaint.
+        assert_eq!(schedule(&event, 5, now), Repaint::Ignore);
+
+        let event = RepaintEvent {
+            viewport_id: ViewportId::from_hash_of("popup"),
+            ..event
+        };
+        assert_eq!(schedule(&event, 3, now), Repaint::Ignore);
+    }
+}
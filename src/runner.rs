@@ -0,0 +1,308 @@
+//! A minimal event loop that runs an egui application in a winit window.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use egui::{Color32, ColorImage, Context};
+use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalSize;
+use winit::error::EventLoopError;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
+use winit::window::{Window, WindowAttributes, WindowId};
+
+use crate::{FrameOutput, Platform, PlatformDescriptor, RepaintEvent};
+
+/// An application that is run by a [`Runner`].
+pub trait App {
+    /// Builds the UI of a frame.
+    fn update(&mut self, context: &Context);
+
+    /// Called once before the window is closed.
+    fn on_exit(&mut self) {}
+}
+
+/// Paints the frames of a [`Runner`].
+pub trait Renderer {
+    /// Called once the window was created.
+    fn init(&mut self, _window: &Arc<Window>) {}
+
+    /// Called when the inner size of the window changed.
+    fn resize(&mut self, _size: PhysicalSize<u32>) {}
+
+    /// Applies the texture changes and paints the frame. If the frame has
+    /// [`FrameOutput::screenshot_requests`], returns an RGBA image of the painted frame, which is
+    /// delivered to egui once per request.
+    fn render(&mut self, frame: &FrameOutput) -> Option<ColorImage>;
+}
+
+/// A renderer that doesn't paint anything. It's meant for tests and keeps the last frame.
+/// Screenshots are transparent images of the screen size.
+#[derive(Default)]
+pub struct HeadlessRenderer {
+    /// The number of rendered frames.
+    pub frames: usize,
+    /// The last rendered frame.
+    pub last_frame: Option<FrameOutput>,
+}
+
+impl Renderer for HeadlessRenderer {
+    fn render(&mut self, frame: &FrameOutput) -> Option<ColorImage> {
+        self.frames += 1;
+        self.last_frame = Some(frame.clone());
+        if frame.screenshot_requests.is_empty() {
+            return None;
+        }
+        let [width, height] = frame.screen_size_in_pixels;
+        let size = [width as usize, height as usize];
+        Some(ColorImage::new(
+            size,
+            vec![Color32::TRANSPARENT; size[0] * size[1]],
+        ))
+    }
+}
+
+/// Runs an [`App`] in a winit window and paints it with a [`Renderer`], like a tiny eframe.
+///
+/// The window is repainted after input that egui receives and whenever egui requests it, also
+/// from other threads. Use [`Self::run`] to open the window, or [`Self::headless`] and
+/// [`Self::step`] to run frames without one.
+pub struct Runner<A, R> {
+    app: A,
+    renderer: R,
+    window_attributes: WindowAttributes,
+    descriptor: PlatformDescriptor,
+    window: Option<Arc<Window>>,
+    platform: Option<Platform>,
+    proxy: Option<EventLoopProxy<RepaintEvent>>,
+    start_time: Instant,
+    next_repaint: Option<Instant>,
+}
+
+impl<A: App, R: Renderer> Runner<A, R> {
+    /// Creates a runner with the default window attributes.
+    pub fn new(app: A, renderer: R) -> Self {
+        Self {
+            app,
+            renderer,
+            window_attributes: Window::default_attributes().with_title("egui"),
+            descriptor: PlatformDescriptor::default(),
+            window: None,
+            platform: None,
+            proxy: None,
+            start_time: Instant::now(),
+            next_repaint: None,
+        }
+    }
+
+    /// Creates a runner without a window. The [`Platform`] is created from the descriptor right
+    /// away and frames are rendered with [`Self::step`].
+    pub fn headless(app: A, renderer: R, descriptor: PlatformDescriptor) -> Self {
+        let mut runner = Self::new(app, renderer);
+        runner.platform = Some(Platform::new(descriptor));
+        runner
+    }
+
+    /// Sets the attributes of the window.
+    pub fn with_window_attributes(mut self, window_attributes: WindowAttributes) -> Self {
+        self.window_attributes = window_attributes;
+        self
+    }
+
+    /// Sets the descriptor of the [`Platform`]. The size and scale factor are taken from the
+    /// window once it's created.
+    pub fn with_descriptor(mut self, descriptor: PlatformDescriptor) -> Self {
+        self.descriptor = descriptor;
+        self
+    }
+
+    /// Opens the window and runs the event loop until the window is closed.
+    pub fn run(mut self) -> Result<(), EventLoopError> {
+        let event_loop = EventLoop::with_user_event().build()?;
+        self.proxy = Some(event_loop.create_proxy());
+        event_loop.run_app(&mut self)
+    }
+
+    /// Renders a frame and returns the time after which egui wants the next one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the platform wasn't created yet, see [`Self::headless`].
+    pub fn step(&mut self) -> Duration {
+        let platform = self
+            .platform
+            .as_mut()
+            .expect("the platform is created with the window");
+        let (repaint_delay, _) = frame(
+            platform,
+            &mut self.app,
+            &mut self.renderer,
+            self.window.as_deref(),
+            self.start_time,
+        );
+        repaint_delay
+    }
+
+    /// Answers the requests of the automation server, see [`Platform::start_automation`].
+    /// This happens automatically while the event loop runs.
+    #[cfg(feature = "automation")]
+    pub fn process_automation(&mut self) {
+        let (app, renderer, window, start_time) = (
+            &mut self.app,
+            &mut self.renderer,
+            self.window.as_deref(),
+            self.start_time,
+        );
+        if let Some(platform) = &mut self.platform {
+            platform.process_automation(|platform| {
+                frame(platform, app, renderer, window, start_time).1
+            });
+        }
+    }
+
+    /// Returns the platform, which is `None` until the window was created.
+    pub fn platform_mut(&mut self) -> Option<&mut Platform> {
+        self.platform.as_mut()
+    }
+
+    /// Returns the application.
+    pub fn app(&self) -> &A {
+        &self.app
+    }
+
+    /// Returns the renderer.
+    pub fn renderer(&self) -> &R {
+        &self.renderer
+    }
+
+    fn redraw(&mut self) {
+        let repaint_delay = self.step();
+        let window = match &self.window {
+            Some(window) => window,
+            None => return,
+        };
+        if repaint_delay.is_zero() {
+            self.next_repaint = None;
+            window.request_redraw();
+        } else {
+            self.next_repaint = Instant::now().checked_add(repaint_delay);
+        }
+    }
+}
+
+impl<A: App, R: Renderer> ApplicationHandler<RepaintEvent> for Runner<A, R> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        let window = match event_loop.create_window(self.window_attributes.clone()) {
+            Ok(window) => Arc::new(window),
+            Err(err) => {
+                eprintln!("Failed to create the window: {}", err);
+                event_loop.exit();
+                return;
+            }
+        };
+
+        if self.platform.is_none() {
+            let size = window.inner_size();
+            let mut platform = Platform::new(PlatformDescriptor {
+                physical_width: size.width,
+                physical_height: size.height,
+                scale_factor: window.scale_factor(),
+                ..std::mem::take(&mut self.descriptor)
+            });
+            if let Some(proxy) = self.proxy.take() {
+                platform.set_repaint_proxy(proxy);
+            }
+            self.platform = Some(platform);
+        }
+        self.renderer.init(&window);
+        window.request_redraw();
+        self.window = Some(window);
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let response = match &mut self.platform {
+            Some(platform) => platform.handle_event(&event),
+            None => Default::default(),
+        };
+
+        match event {
+            WindowEvent::CloseRequested => {
+                self.app.on_exit();
+                event_loop.exit();
+            }
+            WindowEvent::RedrawRequested => self.redraw(),
+            WindowEvent::Resized(size) => {
+                self.renderer.resize(size);
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
+            // Other events only need a frame if egui received input from them. Repaints that
+            // egui requests itself are scheduled by the repaint delay of the last frame.
+            _ if response.repaint => {
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: RepaintEvent) {
+        let (platform, window) = match (&self.platform, &self.window) {
+            (Some(platform), Some(window)) => (platform, window),
+            _ => return,
+        };
+        if let Some(when) = platform.handle_repaint_event(&event, window) {
+            self.next_repaint = Some(self.next_repaint.map_or(when, |next| next.min(when)));
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        #[cfg(feature = "automation")]
+        self.process_automation();
+
+        match self.next_repaint {
+            Some(time) if time <= Instant::now() => {
+                self.next_repaint = None;
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
+                event_loop.set_control_flow(ControlFlow::Wait);
+            }
+            Some(time) => event_loop.set_control_flow(ControlFlow::WaitUntil(time)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
+    }
+}
+
+/// Runs and renders a frame of the application. Returns the time after which egui wants the
+/// next frame and the screenshot the renderer captured.
+fn frame<A: App, R: Renderer>(
+    platform: &mut Platform,
+    app: &mut A,
+    renderer: &mut R,
+    window: Option<&Window>,
+    start_time: Instant,
+) -> (Duration, Option<ColorImage>) {
+    platform.update_time(start_time.elapsed().as_secs_f64());
+    platform.begin_pass();
+    app.update(&platform.context());
+    let frame = platform.end_pass_tessellated(window);
+
+    let image = renderer.render(&frame);
+    if let Some(image) = &image {
+        for user_data in frame.screenshot_requests.iter().cloned() {
+            platform.deliver_screenshot(image.clone(), user_data);
+        }
+    }
+    (frame.repaint_delay, image)
+}
@@ -0,0 +1,310 @@
+//! Renders egui frames on the CPU.
+
+use egui::epaint::{ClippedPrimitive, Primitive, Vertex};
+use egui::{
+    Color32, ColorImage, Context, FullOutput, Rect, TextureFilter, TextureOptions, TextureWrapMode,
+    TexturesDelta,
+};
+
+use crate::{TextureBackend, TextureManager};
+
+/// A texture on the CPU with premultiplied alpha.
+struct Texture {
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+    options: TextureOptions,
+}
+
+/// Keeps the textures in memory.
+struct CpuTextures;
+
+impl TextureBackend for CpuTextures {
+    type Texture = Texture;
+
+    fn create_texture(&mut self, image: &ColorImage, options: TextureOptions) -> Texture {
+        Texture {
+            size: image.size,
+            pixels: image.pixels.clone(),
+            options,
+        }
+    }
+
+    fn update_texture(&mut self, texture: &mut Texture, [x, y]: [usize; 2], image: &ColorImage) {
+        for row in 0..image.size[1] {
+            let src = row * image.size[0];
+            let dst = (y + row) * texture.size[0] + x;
+            texture.pixels[dst..dst + image.size[0]]
+                .copy_from_slice(&image.pixels[src..src + image.size[0]]);
+        }
+    }
+
+    fn set_texture_options(&mut self, texture: &mut Texture, options: TextureOptions) {
+        texture.options = options;
+    }
+
+    fn free_texture(&mut self, _texture: Texture) {}
+}
+
+/// Rasterizes the output of [`crate::Platform::end_pass`] into an image without a GPU.
+///
+/// Colors are blended with premultiplied alpha in gamma space, like the GPU renderers of
+/// egui do. Paint callbacks aren't supported and are skipped.
+pub struct SoftRenderer {
+    textures: TextureManager<CpuTextures>,
+    clear_color: Color32,
+}
+
+impl Default for SoftRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoftRenderer {
+    /// Creates a renderer that clears the image with transparent black.
+    pub fn new() -> Self {
+        Self {
+            textures: TextureManager::new(CpuTextures),
+            clear_color: Color32::TRANSPARENT,
+        }
+    }
+
+    /// Sets the color the image is cleared with before painting.
+    pub fn with_clear_color(mut self, clear_color: Color32) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// Renders the frame into an RGBA image with premultiplied alpha. The image covers the
+    /// viewport of the `context` at the `pixels_per_point` of the frame.
+    pub fn render(&mut self, context: &Context, output: &FullOutput) -> ColorImage {
+        let pixels_per_point = output.pixels_per_point;
+        let [width, height] = crate::frame::screen_size_in_pixels(context, pixels_per_point);
+        let size = [width as usize, height as usize];
+
+        self.update_textures(&output.textures_delta);
+        let primitives = context.tessellate(output.shapes.clone(), pixels_per_point);
+        let image = self.paint(&primitives, pixels_per_point, size);
+        self.free_textures();
+
+        image
+    }
+
+    /// Allocates and updates the textures of the delta. Must be called before painting.
+    pub fn update_textures(&mut self, delta: &TexturesDelta) {
+        self.textures.update(delta);
+    }
+
+    /// Frees the textures egui doesn't need anymore. Must be called after painting.
+    pub fn free_textures(&mut self) {
+        self.textures.end_frame();
+    }
+
+    /// Rasterizes the tessellated primitives into an image of `size` pixels.
+    pub fn paint(
+        &self,
+        primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        size: [usize; 2],
+    ) -> ColorImage {
+        let mut target = Target {
+            size,
+            pixels: vec![rgba(self.clear_color); size[0] * size[1]],
+        };
+
+        for primitive in primitives {
+            let mesh = match &primitive.primitive {
+                Primitive::Mesh(mesh) => mesh,
+                Primitive::Callback(_) => continue,
+            };
+            let texture = match self.textures.get(mesh.texture_id) {
+                Some(texture) => &texture.texture,
+                None => continue,
+            };
+
+            let clip = primitive.clip_rect;
+            let to_pixel =
+                |x: f32, len: usize| ((x * pixels_per_point).round().max(0.0) as usize).min(len);
+            let clip = [
+                to_pixel(clip.min.x, size[0]),
+                to_pixel(clip.min.y, size[1]),
+                to_pixel(clip.max.x, size[0]),
+                to_pixel(clip.max.y, size[1]),
+            ];
+
+            for triangle in mesh.indices.chunks_exact(3) {
+                let vertex = |index: u32| {
+                    let mut vertex = mesh.vertices[index as usize];
+                    vertex.pos = (vertex.pos.to_vec2() * pixels_per_point).to_pos2();
+                    vertex
+                };
+                target.fill_triangle(
+                    [
+                        vertex(triangle[0]),
+                        vertex(triangle[1]),
+                        vertex(triangle[2]),
+                    ],
+                    texture,
+                    clip,
+                );
+            }
+        }
+
+        ColorImage::new(
+            size,
+            target
+                .pixels
+                .iter()
+                .map(|[r, g, b, a]| {
+                    let channel = |c: f32| c.round().clamp(0.0, 255.0) as u8;
+                    Color32::from_rgba_premultiplied(
+                        channel(*r),
+                        channel(*g),
+                        channel(*b),
+                        channel(*a),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The image that is painted into. The channels are premultiplied and in `0.0..=255.0`.
+struct Target {
+    size: [usize; 2],
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Target {
+    /// Fills the pixels whose centers are inside the triangle and the clip rectangle. Pixels on
+    /// the edges between two triangles are only filled once (top-left rule).
+    fn fill_triangle(&mut self, mut vertices: [Vertex; 3], texture: &Texture, clip: [usize; 4]) {
+        let mut area = edge(vertices[0].pos, vertices[1].pos, vertices[2].pos);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        if area < 0.0 {
+            vertices.swap(1, 2);
+            area = -area;
+        }
+        let [v0, v1, v2] = vertices;
+
+        // Textures are minified if the triangle covers fewer pixels than texels.
+        let uv_area =
+            edge(v0.uv, v1.uv, v2.uv).abs() * texture.size[0] as f32 * texture.size[1] as f32;
+        let filter = if uv_area > area {
+            texture.options.minification
+        } else {
+            texture.options.magnification
+        };
+
+        // The pixels covered by the triangle within the clip rectangle.
+        let bounds = Rect::from_points(&[v0.pos, v1.pos, v2.pos]);
+        let rows = bounds.min.y.floor().max(clip[1] as f32) as usize
+            ..(bounds.max.y.ceil().max(0.0) as usize).min(clip[3]);
+        let columns = bounds.min.x.floor().max(clip[0] as f32) as usize
+            ..(bounds.max.x.ceil().max(0.0) as usize).min(clip[2]);
+
+        let edges = [(v1.pos, v2.pos), (v2.pos, v0.pos), (v0.pos, v1.pos)];
+        for y in rows {
+            for x in columns.clone() {
+                let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                let mut weights = [0.0; 3];
+                let inside = edges.iter().zip(&mut weights).all(|((a, b), weight)| {
+                    *weight = edge(*a, *b, p);
+                    *weight > 0.0 || (*weight == 0.0 && is_top_left(*a, *b))
+                });
+                if !inside {
+                    continue;
+                }
+                let [w0, w1, w2] = weights.map(|w| w / area);
+
+                let color = |i: usize| {
+                    let [c0, c1, c2] =
+                        [v0.color, v1.color, v2.color].map(|c| c.to_array()[i] as f32);
+                    c0 * w0 + c1 * w1 + c2 * w2
+                };
+                let uv = egui::pos2(
+                    v0.uv.x * w0 + v1.uv.x * w1 + v2.uv.x * w2,
+                    v0.uv.y * w0 + v1.uv.y * w1 + v2.uv.y * w2,
+                );
+                let texel = texture.sample(uv, filter);
+                let src = [0, 1, 2, 3].map(|i| color(i) * texel[i] / 255.0);
+
+                let dst = &mut self.pixels[y * self.size[0] + x];
+                let inverse_alpha = 1.0 - src[3] / 255.0;
+                *dst = [0, 1, 2, 3].map(|i| src[i] + dst[i] * inverse_alpha);
+            }
+        }
+    }
+}
+
+impl Texture {
+    /// Returns the color at the given texture coordinates.
+    fn sample(&self, uv: egui::Pos2, filter: TextureFilter) -> [f32; 4] {
+        let [width, height] = self.size;
+        if width == 0 || height == 0 {
+            return [0.0; 4];
+        }
+        let x = uv.x * width as f32;
+        let y = uv.y * height as f32;
+        let texel = |x: isize, y: isize| {
+            let x = wrap(x, width, self.options.wrap_mode);
+            let y = wrap(y, height, self.options.wrap_mode);
+            rgba(self.pixels[y * width + x])
+        };
+
+        match filter {
+            TextureFilter::Nearest => texel(x.floor() as isize, y.floor() as isize),
+            TextureFilter::Linear => {
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                let [a, b, c, d] = [
+                    texel(x0, y0),
+                    texel(x0 + 1, y0),
+                    texel(x0, y0 + 1),
+                    texel(x0 + 1, y0 + 1),
+                ];
+                [0, 1, 2, 3].map(|i| {
+                    let top = a[i] + (b[i] - a[i]) * tx;
+                    let bottom = c[i] + (d[i] - c[i]) * tx;
+                    top + (bottom - top) * ty
+                })
+            }
+        }
+    }
+}
+
+/// Maps a texel coordinate into the texture.
+fn wrap(i: isize, len: usize, mode: TextureWrapMode) -> usize {
+    let len = len as isize;
+    let i = match mode {
+        TextureWrapMode::ClampToEdge => i.clamp(0, len - 1),
+        TextureWrapMode::Repeat => i.rem_euclid(len),
+        TextureWrapMode::MirroredRepeat => {
+            let i = i.rem_euclid(2 * len);
+            if i < len {
+                i
+            } else {
+                2 * len - 1 - i
+            }
+        }
+    };
+    i as usize
+}
+
+/// Is positive if `p` is on the inner side of the edge from `a` to `b`.
+fn edge(a: egui::Pos2, b: egui::Pos2, p: egui::Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Is the edge a top edge or a left edge of a triangle with positive area?
+fn is_top_left(a: egui::Pos2, b: egui::Pos2) -> bool {
+    (a.y == b.y && b.x > a.x) || b.y < a.y
+}
+
+fn rgba(color: Color32) -> [f32; 4] {
+    color.to_array().map(f32::from)
+}
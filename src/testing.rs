@@ -0,0 +1,185 @@
+//! Finds widgets in the AccessKit tree and simulates user interactions with them.
+
+use egui::accesskit::{Node, NodeId, Role, Toggled};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+
+use crate::access_tree::{self, AccessTree};
+use crate::Platform;
+
+/// The number of frames [`Platform::run_until_settled`] runs at most.
+pub(crate) const MAX_SETTLE_FRAMES: usize = 100;
+
+/// The state of a widget in the AccessKit tree of the last frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WidgetInfo {
+    /// The ID of the AccessKit node.
+    pub id: NodeId,
+    /// The kind of widget.
+    pub role: Role,
+    /// The text that describes the widget, e.g. the text of a button or label, or the label
+    /// a text edit is labelled by.
+    pub label: Option<String>,
+    /// The text value, e.g. of a text edit.
+    pub value: Option<String>,
+    /// The numeric value, e.g. of a slider.
+    pub numeric_value: Option<f64>,
+    /// The area of the widget in points.
+    pub rect: Option<egui::Rect>,
+    /// The checked state of checkboxes and toggle buttons.
+    pub toggled: Option<Toggled>,
+    /// Whether the widget is disabled.
+    pub disabled: bool,
+    /// Whether the widget has the keyboard focus.
+    pub focused: bool,
+}
+
+impl WidgetInfo {
+    pub(crate) fn new(tree: &AccessTree, id: NodeId, node: &Node) -> Self {
+        Self {
+            id,
+            role: node.role(),
+            label: tree.label(node),
+            value: node.value().map(str::to_owned),
+            numeric_value: node.numeric_value(),
+            rect: access_tree::bounds(node),
+            toggled: node.toggled(),
+            disabled: node.is_disabled(),
+            focused: tree.focus() == Some(id),
+        }
+    }
+}
+
+/// A widget that was found with [`Platform::find_by_label`] or [`Platform::query_by_label`].
+///
+/// The interactions are sent as the winit events a user would cause and are handled by the
+/// next frame, e.g. [`Platform::run_until_settled`]. The assertions check the state of the
+/// widget in the last frame.
+pub struct Widget<'a> {
+    platform: &'a mut Platform,
+    info: WidgetInfo,
+}
+
+impl<'a> Widget<'a> {
+    pub(crate) fn new(platform: &'a mut Platform, info: WidgetInfo) -> Self {
+        Self { platform, info }
+    }
+
+    /// Returns the state of the widget.
+    pub fn info(&self) -> &WidgetInfo {
+        &self.info
+    }
+
+    /// Moves the pointer to the center of the widget.
+    #[track_caller]
+    pub fn hover(&mut self) -> &mut Self {
+        let center = self.center();
+        let scale_factor = self.platform.scale_factor;
+        self.platform.handle_event(&WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new(
+                center.x as f64 * scale_factor,
+                center.y as f64 * scale_factor,
+            ),
+        });
+        self
+    }
+
+    /// Clicks the center of the widget with the primary mouse button.
+    #[track_caller]
+    pub fn click(&mut self) -> &mut Self {
+        self.click_button(MouseButton::Left)
+    }
+
+    /// Clicks the center of the widget with the secondary mouse button.
+    #[track_caller]
+    pub fn secondary_click(&mut self) -> &mut Self {
+        self.click_button(MouseButton::Right)
+    }
+
+    /// Clicks the widget to focus it and types the text.
+    #[track_caller]
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        self.click();
+        self.platform.type_text(text);
+        self
+    }
+
+    #[track_caller]
+    fn click_button(&mut self, button: MouseButton) -> &mut Self {
+        self.hover();
+        for state in [ElementState::Pressed, ElementState::Released] {
+            self.platform.handle_event(&WindowEvent::MouseInput {
+                device_id: DeviceId::dummy(),
+                state,
+                button,
+            });
+        }
+        self
+    }
+
+    #[track_caller]
+    fn center(&self) -> egui::Pos2 {
+        match self.info.rect {
+            Some(rect) => rect.center(),
+            None => panic!("{} has no area to interact with", self.describe()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match &self.info.label {
+            Some(label) => format!("{:?} {:?}", self.info.role, label),
+            None => format!("{:?}", self.info.role),
+        }
+    }
+
+    /// Asserts that the widget is enabled.
+    #[track_caller]
+    pub fn assert_enabled(&mut self) -> &mut Self {
+        assert!(!self.info.disabled, "{} is disabled", self.describe());
+        self
+    }
+
+    /// Asserts that the widget is disabled.
+    #[track_caller]
+    pub fn assert_disabled(&mut self) -> &mut Self {
+        assert!(self.info.disabled, "{} is enabled", self.describe());
+        self
+    }
+
+    /// Asserts that the checkbox or toggle button is checked or unchecked.
+    #[track_caller]
+    pub fn assert_toggled(&mut self, toggled: bool) -> &mut Self {
+        let expected = if toggled {
+            Toggled::True
+        } else {
+            Toggled::False
+        };
+        assert_eq!(
+            self.info.toggled,
+            Some(expected),
+            "{} has the wrong checked state",
+            self.describe()
+        );
+        self
+    }
+
+    /// Asserts the text value of the widget.
+    #[track_caller]
+    pub fn assert_value(&mut self, value: &str) -> &mut Self {
+        assert_eq!(
+            self.info.value.as_deref(),
+            Some(value),
+            "{} has the wrong value",
+            self.describe()
+        );
+        self
+    }
+
+    /// Asserts that the widget has the keyboard focus.
+    #[track_caller]
+    pub fn assert_focused(&mut self) -> &mut Self {
+        assert!(self.info.focused, "{} isn't focused", self.describe());
+        self
+    }
+}
@@ -0,0 +1,132 @@
+//! Bookkeeping of the textures egui requests through `TexturesDelta`.
+
+use std::collections::HashMap;
+
+use egui::{ColorImage, ImageData, TextureId, TextureOptions, TexturesDelta};
+
+/// Creates, updates and frees the textures of a renderer. Used by [`TextureManager`].
+///
+/// The images are RGBA with premultiplied alpha.
+pub trait TextureBackend {
+    /// The texture of the renderer.
+    type Texture;
+
+    /// Creates a texture with the given image.
+    fn create_texture(&mut self, image: &ColorImage, options: TextureOptions) -> Self::Texture;
+
+    /// Replaces the region of the texture at `pos` with the given image. The region is
+    /// always inside of the texture.
+    fn update_texture(&mut self, texture: &mut Self::Texture, pos: [usize; 2], image: &ColorImage);
+
+    /// Changes the sampling options of the texture.
+    fn set_texture_options(&mut self, texture: &mut Self::Texture, options: TextureOptions);
+
+    /// Frees the texture.
+    fn free_texture(&mut self, texture: Self::Texture);
+}
+
+/// A texture that is managed by a [`TextureManager`].
+#[derive(Debug)]
+pub struct ManagedTexture<T> {
+    /// The texture of the backend.
+    pub texture: T,
+    /// The size of the texture in texels.
+    pub size: [usize; 2],
+    /// The sampling options of the texture.
+    pub options: TextureOptions,
+}
+
+/// Applies the `textures_delta` of [`egui::FullOutput`] to the textures of a renderer.
+///
+/// Call [`Self::update`] before painting a frame and [`Self::end_frame`] after it, which frees
+/// the textures egui doesn't need anymore.
+pub struct TextureManager<B: TextureBackend> {
+    backend: B,
+    textures: HashMap<TextureId, ManagedTexture<B::Texture>>,
+    pending_free: Vec<TextureId>,
+}
+
+impl<B: TextureBackend> TextureManager<B> {
+    /// Creates a manager without any textures.
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            textures: HashMap::new(),
+            pending_free: Vec::new(),
+        }
+    }
+
+    /// Returns the backend.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the backend for modification.
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    /// Returns the texture with the given ID.
+    pub fn get(&self, id: TextureId) -> Option<&ManagedTexture<B::Texture>> {
+        self.textures.get(&id)
+    }
+
+    /// Returns the IDs of all textures.
+    pub fn ids(&self) -> impl Iterator<Item = TextureId> + '_ {
+        self.textures.keys().copied()
+    }
+
+    /// Creates and updates the textures of the delta in the order egui sent them. The textures
+    /// that should be freed are kept until [`Self::end_frame`], since they may still be used by
+    /// the frame.
+    pub fn update(&mut self, delta: &TexturesDelta) {
+        for (id, image_delta) in &delta.set {
+            let ImageData::Color(image) = &image_delta.image;
+            match image_delta.pos {
+                None => {
+                    let texture = ManagedTexture {
+                        texture: self.backend.create_texture(image, image_delta.options),
+                        size: image.size,
+                        options: image_delta.options,
+                    };
+                    if let Some(old) = self.textures.insert(*id, texture) {
+                        self.backend.free_texture(old.texture);
+                    }
+                }
+                Some(pos) => {
+                    let texture = match self.textures.get_mut(id) {
+                        Some(texture) => texture,
+                        None => {
+                            eprintln!("Can't update unknown texture {:?}", id);
+                            continue;
+                        }
+                    };
+                    if pos[0] + image.size[0] > texture.size[0]
+                        || pos[1] + image.size[1] > texture.size[1]
+                    {
+                        eprintln!("Texture update of {:?} is out of bounds", id);
+                        continue;
+                    }
+                    if texture.options != image_delta.options {
+                        self.backend
+                            .set_texture_options(&mut texture.texture, image_delta.options);
+                        texture.options = image_delta.options;
+                    }
+                    self.backend
+                        .update_texture(&mut texture.texture, pos, image);
+                }
+            }
+        }
+
+        self.pending_free.extend_from_slice(&delta.free);
+    }
+
+    /// Frees the textures of the deltas that were applied since the last call.
+    pub fn end_frame(&mut self) {
+        for id in self.pending_free.drain(..) {
+            if let Some(texture) = self.textures.remove(&id) {
+                self.backend.free_texture(texture.texture);
+            }
+        }
+    }
+}
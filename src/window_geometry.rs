@@ -0,0 +1,301 @@
+//! Saves and restores the size and position of the native window.
+
+use std::cmp::Reverse;
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowAttributes};
+
+/// The state of the native window that is needed to reopen it in the same place.
+///
+/// It's tracked by [`crate::Platform::handle_event`] and
+/// [`crate::Platform::capture_window_geometry`]. With the `persistence` feature, it can be
+/// serialized and is saved together with the `Storage`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "persistence",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct WindowGeometry {
+    /// The outer position of the window in physical pixel. `None` if the platform doesn't
+    /// report window positions.
+    pub position: Option<[i32; 2]>,
+    /// The inner size of the window in physical pixel.
+    pub inner_size: [u32; 2],
+    /// The HiDPI scale factor at the time the geometry was captured.
+    pub scale_factor: f64,
+    /// The name of the monitor the window was on.
+    pub monitor: Option<String>,
+    /// Whether the window was maximized.
+    pub maximized: bool,
+    /// Whether the window was fullscreen.
+    pub fullscreen: bool,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            position: None,
+            inner_size: [800, 600],
+            scale_factor: 1.0,
+            monitor: None,
+            maximized: false,
+            fullscreen: false,
+        }
+    }
+}
+
+impl WindowGeometry {
+    /// Captures the current geometry of the window.
+    pub fn from_window(window: &Window) -> Self {
+        let size = window.inner_size();
+        Self {
+            position: window.outer_position().ok().map(|pos| [pos.x, pos.y]),
+            inner_size: [size.width, size.height],
+            scale_factor: window.scale_factor(),
+            monitor: window.current_monitor().and_then(|monitor| monitor.name()),
+            maximized: window.is_maximized(),
+            fullscreen: window.fullscreen().is_some(),
+        }
+    }
+
+    /// Loads the geometry that was saved by [`crate::Platform::save`].
+    #[cfg(feature = "persistence")]
+    pub fn load(storage: &crate::Storage) -> Option<Self> {
+        storage.get_value(crate::persistence::WINDOW_GEOMETRY_KEY)
+    }
+
+    /// Applies the geometry to the attributes of a new window. The window is placed on the
+    /// monitor it was on if it's still available, otherwise on the monitor it overlaps the most.
+    /// Its size and position are clamped to the monitor, so that it's completely visible.
+    ///
+    /// `monitors` should be the monitors available at startup, see
+    /// [`winit::event_loop::ActiveEventLoop::available_monitors`].
+    pub fn window_attributes(
+        &self,
+        attributes: WindowAttributes,
+        monitors: impl IntoIterator<Item = MonitorHandle>,
+    ) -> WindowAttributes {
+        let monitors: Vec<MonitorHandle> = monitors.into_iter().collect();
+        let areas: Vec<MonitorArea> = monitors.iter().map(MonitorArea::new).collect();
+        let placement = place(self, &areas);
+        let monitor = placement.monitor.map(|index| monitors[index].clone());
+
+        let [width, height] = placement.size;
+        let mut attributes = attributes
+            .with_inner_size(PhysicalSize::new(width, height))
+            .with_maximized(self.maximized);
+        if let Some([x, y]) = placement.position {
+            attributes = attributes.with_position(PhysicalPosition::new(x, y));
+        }
+        if self.fullscreen {
+            attributes = attributes.with_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        }
+        attributes
+    }
+}
+
+/// The part of a monitor that is needed to place a window on it.
+#[derive(Clone, Debug)]
+struct MonitorArea {
+    name: Option<String>,
+    position: [i32; 2],
+    size: [u32; 2],
+    scale_factor: f64,
+}
+
+impl MonitorArea {
+    fn new(monitor: &MonitorHandle) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+        Self {
+            name: monitor.name(),
+            position: [position.x, position.y],
+            size: [size.width, size.height],
+            scale_factor: monitor.scale_factor(),
+        }
+    }
+}
+
+/// Where a window is placed by [`WindowGeometry::window_attributes`].
+#[derive(Debug, PartialEq)]
+struct Placement {
+    /// The index of the monitor.
+    monitor: Option<usize>,
+    size: [u32; 2],
+    position: Option<[i32; 2]>,
+}
+
+/// Chooses the monitor of the window and clamps its size and position to it.
+fn place(geometry: &WindowGeometry, monitors: &[MonitorArea]) -> Placement {
+    let monitor = monitors
+        .iter()
+        .position(|monitor| geometry.monitor.is_some() && monitor.name == geometry.monitor)
+        .or_else(|| {
+            let position = geometry.position?;
+            // The first monitor wins a tie, e.g. if the window overlaps none of them.
+            (0..monitors.len()).max_by_key(|index| {
+                let monitor = &monitors[*index];
+                let area = overlap(
+                    position,
+                    geometry.inner_size,
+                    monitor.position,
+                    monitor.size,
+                );
+                (area, Reverse(*index))
+            })
+        })
+        .or_else(|| (!monitors.is_empty()).then_some(0));
+
+    let mut size = geometry.inner_size;
+    let mut position = geometry.position;
+    if let Some(monitor) = monitor.map(|index| &monitors[index]) {
+        // Keeps the logical size if the monitor has a different scale factor now.
+        let scale =
+            valid_scale_factor(monitor.scale_factor) / valid_scale_factor(geometry.scale_factor);
+        let [width, height] = monitor.size;
+        size = [
+            ((size[0] as f64 * scale).round() as u32).clamp(1, width.max(1)),
+            ((size[1] as f64 * scale).round() as u32).clamp(1, height.max(1)),
+        ];
+        position = position.map(|[x, y]| {
+            let [monitor_x, monitor_y] = monitor.position;
+            let max_x = monitor_x.saturating_add(width.saturating_sub(size[0]) as i32);
+            let max_y = monitor_y.saturating_add(height.saturating_sub(size[1]) as i32);
+            [x.clamp(monitor_x, max_x), y.clamp(monitor_y, max_y)]
+        });
+    }
+
+    Placement {
+        monitor,
+        size,
+        position,
+    }
+}
+
+/// Returns the scale factor, or 1 if it's invalid, e.g. in an edited storage file.
+fn valid_scale_factor(scale_factor: f64) -> f64 {
+    if scale_factor.is_finite() && scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    }
+}
+
+/// Returns the area in which the window overlaps the monitor.
+fn overlap(
+    position: [i32; 2],
+    size: [u32; 2],
+    monitor_pos: [i32; 2],
+    monitor_size: [u32; 2],
+) -> u64 {
+    let axis = |pos: i32, len: u32, monitor_pos: i32, monitor_len: u32| {
+        let start = pos.max(monitor_pos) as i64;
+        let end = (pos as i64 + len as i64).min(monitor_pos as i64 + monitor_len as i64);
+        (end - start).max(0) as u64
+    };
+    axis(position[0], size[0], monitor_pos[0], monitor_size[0])
+        * axis(position[1], size[1], monitor_pos[1], monitor_size[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, position: [i32; 2], size: [u32; 2], scale_factor: f64) -> MonitorArea {
+        MonitorArea {
+            name: Some(name.to_owned()),
+            position,
+            size,
+            scale_factor,
+        }
+    }
+
+    fn geometry(position: [i32; 2], inner_size: [u32; 2], monitor: Option<&str>) -> WindowGeometry {
+        WindowGeometry {
+            position: Some(position),
+            inner_size,
+            monitor: monitor.map(str::to_owned),
+            ..Default::default()
+        }
+    }
+
+    fn monitors() -> Vec<MonitorArea> {
+        vec![
+            monitor("left", [0, 0], [1920, 1080], 1.0),
+            monitor("right", [1920, 0], [2560, 1440], 2.0),
+        ]
+    }
+
+    #[test]
+    fn window_stays_on_its_monitor() {
+        let placement = place(&geometry([100, 50], [400, 300], Some("left")), &monitors());
+        assert_eq!(
+            placement,
+            Placement {
+                monitor: Some(0),
+                size: [400, 300],
+                position: Some([100, 50]),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_monitor_is_replaced_by_the_most_overlapped_one() {
+        let placement = place(
+            &geometry([1800, 100], [400, 300], Some("gone")),
+            &monitors(),
+        );
+        assert_eq!(placement.monitor, Some(1));
+        // The logical size is kept at the scale factor of the new monitor.
+        assert_eq!(placement.size, [800, 600]);
+        assert_eq!(placement.position, Some([1920, 100]));
+
+        let placement = place(&geometry([-5000, 0], [400, 300], None), &monitors());
+        assert_eq!(placement.monitor, Some(0));
+        assert_eq!(placement.position, Some([0, 0]));
+    }
+
+    #[test]
+    fn window_is_clamped_to_the_monitor() {
+        let placement = place(
+            &geometry([1500, 900], [3000, 500], Some("left")),
+            &monitors(),
+        );
+        assert_eq!(placement.size, [1920, 500]);
+        assert_eq!(placement.position, Some([0, 580]));
+    }
+
+    #[test]
+    fn empty_monitor_size_doesnt_overflow() {
+        let monitors = [monitor("empty", [10, 20], [0, 0], 1.0)];
+        let placement = place(&geometry([100, 100], [400, 300], Some("empty")), &monitors);
+        assert_eq!(placement.size, [1, 1]);
+        assert_eq!(placement.position, Some([10, 20]));
+    }
+
+    #[test]
+    fn without_monitors_the_geometry_is_kept() {
+        let placement = place(&geometry([-100, 50], [400, 300], Some("left")), &[]);
+        assert_eq!(
+            placement,
+            Placement {
+                monitor: None,
+                size: [400, 300],
+                position: Some([-100, 50]),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_scale_factor_is_ignored() {
+        for scale_factor in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+            let geometry = WindowGeometry {
+                scale_factor,
+                ..geometry([100, 50], [400, 300], Some("left"))
+            };
+            assert_eq!(place(&geometry, &monitors()).size, [400, 300]);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "accesskit")]
+
+use accesskit_consumer::Tree;
+use egui::accesskit::{Action, ActionRequest, NodeId, Role};
+use egui_winit_platform::{accesskit_winit::WindowEvent, Platform};
+
+mod common;
+
+use common::platform;
+
+fn frame(platform: &mut Platform, clicks: &mut u32) -> egui::FullOutput {
+    common::frame(platform, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if ui.button("Save").clicked() {
+                *clicks += 1;
+            }
+        });
+    })
+}
+
+fn find_by_label(tree: &Tree, label: &str) -> Option<NodeId> {
+    let mut stack = vec![tree.state().root()];
+    while let Some(node) = stack.pop() {
+        if node.label().as_deref() == Some(label) && node.role() == Role::Button {
+            return Some(node.id());
+        }
+        stack.extend(node.children());
+    }
+    None
+}
+
+#[test]
+fn no_tree_update_before_activation() {
+    let mut platform = platform();
+    let output = frame(&mut platform, &mut 0);
+    assert!(output.platform_output.accesskit_update.is_none());
+}
+
+#[test]
+fn consumer_can_click_button() {
+    let mut platform = platform();
+    let mut clicks = 0;
+
+    platform.handle_accesskit_event(&WindowEvent::InitialTreeRequested);
+    let output = frame(&mut platform, &mut clicks);
+    let update = output
+        .platform_output
+        .accesskit_update
+        .expect("egui should produce a tree after activation");
+
+    let tree = Tree::new(update, true);
+    let target = find_by_label(&tree, "Save").expect("button should be in the tree");
+
+    platform.handle_accesskit_event(&WindowEvent::ActionRequested(ActionRequest {
+        action: Action::Click,
+        target,
+        data: None,
+    }));
+    frame(&mut platform, &mut clicks);
+    assert_eq!(clicks, 1);
+
+    platform.handle_accesskit_event(&WindowEvent::AccessibilityDeactivated);
+    let output = frame(&mut platform, &mut clicks);
+    assert!(output.platform_output.accesskit_update.is_none());
+}
@@ -0,0 +1,108 @@
+use egui::Vec2;
+use egui_winit_platform::{KeyInput, Platform};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{DeviceId, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent},
+    keyboard::{Key, KeyCode, KeyLocation, PhysicalKey},
+};
+
+mod common;
+
+use common::{left_button, move_to, platform};
+
+/// Draws an egui area that covers the top left corner, with a text edit that has the focus
+/// if `focus` is set.
+fn frame(platform: &mut Platform, focus: bool) {
+    common::frame(platform, |ctx| {
+        egui::Area::new(egui::Id::new("area"))
+            .fixed_pos(egui::pos2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.set_min_size(egui::vec2(200.0, 200.0));
+                let response = ui.text_edit_singleline(&mut String::new());
+                if focus {
+                    response.request_focus();
+                }
+            });
+    });
+}
+
+fn scroll(platform: &mut Platform, lines: f32) {
+    platform.handle_event(&WindowEvent::MouseWheel {
+        device_id: DeviceId::dummy(),
+        delta: MouseScrollDelta::LineDelta(0.0, lines),
+        phase: TouchPhase::Moved,
+    });
+}
+
+fn key(platform: &mut Platform, state: ElementState) {
+    platform.handle_key_input(&KeyInput {
+        physical_key: PhysicalKey::Code(KeyCode::KeyW),
+        logical_key: Key::Character("w".into()),
+        text: None,
+        location: KeyLocation::Standard,
+        state,
+        repeat: false,
+    });
+}
+
+#[test]
+fn input_outside_of_egui_belongs_to_the_app() {
+    let mut platform = platform();
+    frame(&mut platform, false);
+    move_to(&mut platform, 500.0, 400.0);
+    frame(&mut platform, false);
+
+    left_button(&mut platform, ElementState::Pressed);
+    key(&mut platform, ElementState::Pressed);
+    scroll(&mut platform, 2.0);
+
+    let input = platform.app_input();
+    assert_eq!(
+        input.pointer_pos(),
+        Some(PhysicalPosition::new(500.0, 400.0))
+    );
+    assert!(input.is_mouse_button_down(MouseButton::Left));
+    assert!(input.is_physical_key_down(PhysicalKey::Code(KeyCode::KeyW)));
+    assert!(input.is_logical_key_down(&Key::Character("w".into())));
+    // The scrolling belongs to the next frame.
+    assert_eq!(input.scroll_lines(), Vec2::ZERO);
+
+    frame(&mut platform, false);
+    assert_eq!(platform.app_input().scroll_lines(), Vec2::new(0.0, 2.0));
+
+    // Scrolling only lasts a frame, held keys and buttons until they are released.
+    frame(&mut platform, false);
+    let input = platform.app_input();
+    assert_eq!(input.scroll_lines(), Vec2::ZERO);
+    assert!(input.is_mouse_button_down(MouseButton::Left));
+    assert!(input.is_physical_key_down(PhysicalKey::Code(KeyCode::KeyW)));
+
+    left_button(&mut platform, ElementState::Released);
+    key(&mut platform, ElementState::Released);
+    let input = platform.app_input();
+    assert!(!input.is_mouse_button_down(MouseButton::Left));
+    assert!(!input.is_physical_key_down(PhysicalKey::Code(KeyCode::KeyW)));
+    assert_eq!(input.logical_keys_down().count(), 0);
+}
+
+#[test]
+fn input_consumed_by_egui_is_left_out() {
+    let mut platform = platform();
+    frame(&mut platform, true);
+    move_to(&mut platform, 50.0, 50.0);
+    frame(&mut platform, true);
+
+    left_button(&mut platform, ElementState::Pressed);
+    key(&mut platform, ElementState::Pressed);
+    scroll(&mut platform, 2.0);
+    frame(&mut platform, true);
+    // The button was pressed over egui, so egui also owns the movement.
+    move_to(&mut platform, 60.0, 60.0);
+
+    let input = platform.app_input();
+    assert_eq!(input.pointer_pos(), None);
+    assert!(!input.is_mouse_button_down(MouseButton::Left));
+    assert!(!input.is_physical_key_down(PhysicalKey::Code(KeyCode::KeyW)));
+    assert_eq!(input.logical_keys_down().count(), 0);
+    assert_eq!(input.scroll_lines(), Vec2::ZERO);
+}
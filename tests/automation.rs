@@ -0,0 +1,395 @@
+#![cfg(feature = "automation")]
+
+use std::sync::mpsc;
+
+use egui::{Color32, ColorImage};
+use egui_winit_platform::{
+    AutomationAddress, AutomationClient, KeyAction, KeyChord, Platform, PlatformDescriptor,
+};
+use serde_json::{json, Value};
+use winit::event::MouseButton;
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+#[derive(Default)]
+struct State {
+    clicks: usize,
+    checked: bool,
+    text: String,
+}
+
+fn frame(platform: &mut Platform, state: &mut State) -> Option<ColorImage> {
+    platform.begin_pass();
+    egui::CentralPanel::default().show(&platform.context(), |ui| {
+        if ui.button("Save").clicked() {
+            state.clicks += 1;
+        }
+        ui.checkbox(&mut state.checked, "Enabled");
+        ui.text_edit_singleline(&mut state.text);
+    });
+    let frame = platform.end_pass_tessellated(None);
+    if frame.screenshot_requests.is_empty() {
+        return None;
+    }
+    let [width, height] = frame.screen_size_in_pixels;
+    Some(ColorImage::new(
+        [width as usize, height as usize],
+        vec![Color32::RED; (width * height) as usize],
+    ))
+}
+
+/// Runs the script on a client thread and answers its requests until it's done.
+fn run(
+    state: &mut State,
+    address: AutomationAddress,
+    script: impl FnOnce(&mut AutomationClient) + Send + 'static,
+) -> std::thread::Result<()> {
+    let mut platform = Platform::new(PlatformDescriptor {
+        physical_width: 400,
+        physical_height: 300,
+        scale_factor: 1.0,
+        ..Default::default()
+    });
+    let address = platform.start_automation(&address).unwrap();
+    frame(&mut platform, state);
+
+    let (done, finished) = mpsc::channel::<()>();
+    let client = std::thread::spawn(move || {
+        let mut client = AutomationClient::connect(&address).unwrap();
+        script(&mut client);
+        drop(done);
+    });
+    while finished.recv_timeout(std::time::Duration::from_millis(1))
+        != Err(mpsc::RecvTimeoutError::Disconnected)
+    {
+        platform.process_automation(|platform| frame(platform, state));
+    }
+    client.join()
+}
+
+fn tcp() -> AutomationAddress {
+    AutomationAddress::Tcp("127.0.0.1:0".parse().unwrap())
+}
+
+fn request(client: &mut AutomationClient, request: Value) -> Value {
+    let response = client.request(&request).unwrap();
+    assert_eq!(response["ok"], true, "{}", response);
+    response
+}
+
+fn find<'a>(nodes: &'a [Value], label: &str) -> &'a Value {
+    nodes
+        .iter()
+        .find(|node| node["label"] == label)
+        .unwrap_or_else(|| panic!("no node with the label {}", label))
+}
+
+#[test]
+fn script_clicks_and_types() {
+    let mut state = State::default();
+    run(&mut state, tcp(), |client| {
+        let response = request(client, json!({"id": 1, "command": "tree"}));
+        assert_eq!(response["id"], 1);
+        let nodes = response["nodes"].as_array().unwrap();
+        let save = find(nodes, "Save");
+        assert_eq!(save["role"], "Button");
+        let rect = &save["rect"];
+        let x = (rect[0].as_f64().unwrap() + rect[2].as_f64().unwrap()) / 2.0;
+        let y = (rect[1].as_f64().unwrap() + rect[3].as_f64().unwrap()) / 2.0;
+        assert_eq!(find(nodes, "Enabled")["toggled"], "false");
+
+        request(client, json!({"command": "click", "x": x, "y": y}));
+        request(client, json!({"command": "frames"}));
+        request(
+            client,
+            json!({"command": "click_label", "label": "Enabled"}),
+        );
+        request(client, json!({"command": "frames", "count": 2}));
+
+        let response = request(client, json!({"command": "tree"}));
+        let nodes = response["nodes"].as_array().unwrap();
+        assert_eq!(find(nodes, "Enabled")["toggled"], "true");
+
+        let edit = nodes
+            .iter()
+            .find(|node| node["role"] == "TextInput")
+            .expect("text edit should be in the tree");
+        let rect = &edit["rect"];
+        request(
+            client,
+            json!({
+                "command": "click",
+                "x": rect[0].as_f64().unwrap() + 5.0,
+                "y": rect[1].as_f64().unwrap() + 5.0,
+            }),
+        );
+        request(client, json!({"command": "frames"}));
+        request(client, json!({"command": "text", "text": "héllo \"egui\""}));
+        request(client, json!({"command": "key", "key": "Backspace"}));
+        request(client, json!({"command": "frames"}));
+    })
+    .unwrap();
+
+    assert_eq!(state.clicks, 1);
+    assert!(state.checked);
+    assert_eq!(state.text, "héllo \"egui");
+}
+
+#[test]
+fn script_takes_screenshots() {
+    run(&mut State::default(), tcp(), |client| {
+        let response = request(client, json!({"command": "screenshot"}));
+        assert_eq!(response["width"], 400);
+        assert_eq!(response["height"], 300);
+        let rgba = response["rgba"].as_str().unwrap();
+        assert_eq!(rgba.len(), 400 * 300 * 4 / 3 * 4);
+        assert!(rgba.starts_with("/wAA//8AAP"));
+    })
+    .unwrap();
+}
+
+#[test]
+fn invalid_requests_are_rejected() {
+    run(&mut State::default(), tcp(), |client| {
+        for request in [
+            r#"{"id": "a", "command": "fly"}"#,
+            r#"{"command": "click", "x": 1}"#,
+            r#"{"command": "key", "key": "NoSuchKey"}"#,
+            r#"{"command": "click_label", "label": "Missing"}"#,
+            r#"{"command": "click", "x": 1, "y": 2, "modifiers": ["hyper"]}"#,
+            r#"{"command": "tree""#,
+            r#"{"command": "frames", "count": 1.5}"#,
+            r#"{"command": "frames", "count": -1}"#,
+        ] {
+            let response: Value =
+                serde_json::from_str(&client.request_line(request).unwrap()).unwrap();
+            assert_eq!(response["ok"], false, "{}", request);
+            assert!(response["error"].is_string());
+        }
+        let response = client
+            .request(&json!({"id": "a", "command": "fly"}))
+            .unwrap();
+        assert_eq!(response["id"], "a");
+    })
+    .unwrap();
+}
+
+#[test]
+fn frame_count_is_limited() {
+    let mut frames = 0;
+    let mut platform = Platform::new(PlatformDescriptor::default());
+    let address = platform.start_automation(&tcp()).unwrap();
+    let client = std::thread::spawn(move || {
+        let mut client = AutomationClient::connect(&address).unwrap();
+        request(
+            &mut client,
+            json!({"command": "frames", "count": 1_000_000}),
+        )
+    });
+    while !client.is_finished() {
+        platform.process_automation(|platform| {
+            frames += 1;
+            platform.begin_pass();
+            platform.end_pass(None);
+            None
+        });
+    }
+    assert_eq!(client.join().unwrap()["frames"], 100);
+    assert_eq!(frames, 100);
+}
+
+#[test]
+fn input_is_handled_like_winit_events() {
+    let mut platform = Platform::new(PlatformDescriptor {
+        physical_width: 400,
+        physical_height: 300,
+        scale_factor: 2.0,
+        ..Default::default()
+    });
+    platform.keymap_mut().bind(
+        KeyChord::logical(ModifiersState::CONTROL, egui::Key::S),
+        KeyAction::User("save".to_owned()),
+    );
+    let address = platform.start_automation(&tcp()).unwrap();
+    let client = std::thread::spawn(move || {
+        let mut client = AutomationClient::connect(&address).unwrap();
+        request(
+            &mut client,
+            json!({"command": "key", "key": "S", "modifiers": ["ctrl"]}),
+        );
+        request(
+            &mut client,
+            json!({"command": "pointer_button", "x": 10, "y": 20, "pressed": true}),
+        );
+    });
+    while !client.is_finished() {
+        platform.process_automation(|_| None);
+    }
+    client.join().unwrap();
+
+    assert_eq!(platform.take_triggered_actions(), vec!["save"]);
+    assert!(!platform.raw_input_mut().modifiers.ctrl);
+    assert!(platform.app_input().is_mouse_button_down(MouseButton::Left));
+    assert!(platform
+        .raw_input_mut()
+        .events
+        .contains(&egui::Event::PointerButton {
+            pos: egui::pos2(10.0, 20.0),
+            button: egui::PointerButton::Primary,
+            pressed: true,
+            modifiers: Default::default(),
+        }));
+}
+
+/// Sends the requests from a client thread and answers them without running frames.
+fn send(platform: &mut Platform, requests: Vec<Value>) {
+    let address = platform.start_automation(&tcp()).unwrap();
+    let client = std::thread::spawn(move || {
+        let mut client = AutomationClient::connect(&address).unwrap();
+        for value in requests {
+            request(&mut client, value);
+        }
+    });
+    while !client.is_finished() {
+        platform.process_automation(|_| None);
+    }
+    client.join().unwrap();
+}
+
+#[test]
+fn keys_without_characters_type_no_text() {
+    let mut platform = Platform::new(PlatformDescriptor {
+        physical_width: 400,
+        physical_height: 300,
+        scale_factor: 1.0,
+        ..Default::default()
+    });
+    let keys = ["F21", "Copy", "BrowserBack", "Enter", "A"];
+    send(
+        &mut platform,
+        keys.iter()
+            .map(|key| json!({"command": "key", "key": key}))
+            .collect(),
+    );
+
+    let events = std::mem::take(&mut platform.raw_input_mut().events);
+    let texts: Vec<_> = events
+        .iter()
+        .filter(|event| matches!(event, egui::Event::Text(_)))
+        .collect();
+    assert_eq!(texts, vec![&egui::Event::Text("a".to_owned())]);
+    let pressed: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            egui::Event::Key {
+                key, pressed: true, ..
+            } => Some(key.name()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(pressed, keys);
+}
+
+#[test]
+fn keys_have_their_physical_key() {
+    let mut platform = Platform::new(PlatformDescriptor {
+        physical_width: 400,
+        physical_height: 300,
+        scale_factor: 1.0,
+        ..Default::default()
+    });
+    platform.keymap_mut().bind(
+        KeyChord::physical(ModifiersState::CONTROL, egui::Key::Z),
+        KeyAction::User("undo".to_owned()),
+    );
+    send(
+        &mut platform,
+        vec![
+            json!({"command": "key", "key": "A", "pressed": true}),
+            json!({"command": "key", "key": "B"}),
+            json!({"command": "key", "key": "Z", "modifiers": ["ctrl"]}),
+        ],
+    );
+
+    assert_eq!(platform.take_triggered_actions(), vec!["undo"]);
+    // Releasing B leaves A held.
+    let input = platform.app_input();
+    assert!(input.is_physical_key_down(PhysicalKey::Code(KeyCode::KeyA)));
+    assert!(!input.is_physical_key_down(PhysicalKey::Code(KeyCode::KeyB)));
+}
+
+#[test]
+fn long_requests_are_refused() {
+    run(&mut State::default(), tcp(), |client| {
+        let long = format!(
+            r#"{{"command": "text", "text": "{}"}}"#,
+            "a".repeat(2 << 20)
+        );
+        let response: Value = serde_json::from_str(&client.request_line(&long).unwrap()).unwrap();
+        assert_eq!(response["ok"], false);
+        request(client, json!({"command": "tree"}));
+    })
+    .unwrap();
+}
+
+#[cfg(unix)]
+fn socket_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "egui_automation_{}_{}.sock",
+        name,
+        std::process::id()
+    ))
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_socket() {
+    let path = socket_path("unix");
+    run(
+        &mut State::default(),
+        AutomationAddress::Unix(path.clone()),
+        |client| {
+            request(client, json!({"command": "frames"}));
+        },
+    )
+    .unwrap();
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn stale_unix_socket_is_replaced() {
+    let path = socket_path("stale");
+    // The listener leaves its socket file behind.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let address = AutomationAddress::Unix(path.clone());
+    let mut platform = Platform::new(PlatformDescriptor::default());
+    platform.start_automation(&address).unwrap();
+
+    // The socket of a running server isn't replaced.
+    let mut other = Platform::new(PlatformDescriptor::default());
+    let err = other.start_automation(&address).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+    drop(platform);
+    assert!(!path.exists());
+}
+
+#[test]
+fn dropping_the_platform_stops_the_server() {
+    let mut platform = Platform::new(PlatformDescriptor::default());
+    let address = platform.start_automation(&tcp()).unwrap();
+    let mut client = AutomationClient::connect(&address).unwrap();
+
+    drop(platform);
+    assert!(client.request(&json!({"command": "tree"})).is_err());
+    assert!(AutomationClient::connect(&address).is_err());
+}
+
+#[test]
+fn non_loopback_addresses_are_refused() {
+    let mut platform = Platform::new(PlatformDescriptor::default());
+    let address = AutomationAddress::Tcp("0.0.0.0:0".parse().unwrap());
+    assert!(platform.start_automation(&address).is_err());
+}
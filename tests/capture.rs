@@ -0,0 +1,85 @@
+use egui_winit_platform::{InputOwner, Platform};
+use winit::event::{ElementState, WindowEvent};
+
+mod common;
+
+use common::{cursor_moved, mouse_input, platform};
+
+/// Draws an egui area that covers the top left corner.
+fn frame(platform: &mut Platform) {
+    common::frame(platform, |ctx| {
+        egui::Area::new(egui::Id::new("area"))
+            .fixed_pos(egui::pos2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.allocate_exact_size(egui::vec2(200.0, 200.0), egui::Sense::drag());
+            });
+    });
+}
+
+fn owner(platform: &mut Platform, event: WindowEvent) -> Option<InputOwner> {
+    platform.handle_event(&event).owner
+}
+
+#[test]
+fn drag_from_egui_stays_with_egui() {
+    let mut platform = platform();
+    owner(&mut platform, cursor_moved(50.0, 50.0));
+    frame(&mut platform);
+    frame(&mut platform);
+
+    let press = owner(&mut platform, mouse_input(ElementState::Pressed));
+    frame(&mut platform);
+    let moved = owner(&mut platform, cursor_moved(500.0, 500.0));
+    frame(&mut platform);
+    let release = owner(&mut platform, mouse_input(ElementState::Released));
+
+    assert_eq!(press, Some(InputOwner::Egui));
+    assert_eq!(moved, Some(InputOwner::Egui));
+    assert_eq!(release, Some(InputOwner::Egui));
+}
+
+#[test]
+fn drag_from_app_stays_with_app() {
+    let mut platform = platform();
+    owner(&mut platform, cursor_moved(500.0, 500.0));
+    // Egui needs a frame to know which parts of the screen are covered.
+    frame(&mut platform);
+    frame(&mut platform);
+
+    let press = owner(&mut platform, mouse_input(ElementState::Pressed));
+    frame(&mut platform);
+    let moved = owner(&mut platform, cursor_moved(50.0, 50.0));
+    frame(&mut platform);
+    let release = owner(&mut platform, mouse_input(ElementState::Released));
+    frame(&mut platform);
+    let press_again = owner(&mut platform, mouse_input(ElementState::Pressed));
+
+    assert_eq!(press, Some(InputOwner::App));
+    assert_eq!(moved, Some(InputOwner::App));
+    assert_eq!(release, Some(InputOwner::App));
+    assert_eq!(press_again, Some(InputOwner::Egui));
+}
+
+#[test]
+fn non_input_events_have_no_owner() {
+    let mut platform = platform();
+    assert_eq!(owner(&mut platform, WindowEvent::Focused(true)), None);
+}
+
+#[test]
+fn only_input_of_egui_needs_a_repaint() {
+    let repaint = |platform: &mut Platform, event| platform.handle_event(&event).repaint;
+
+    // Presses without a known pointer position aren't sent to egui.
+    let mut platform = platform();
+    assert!(!repaint(&mut platform, mouse_input(ElementState::Pressed)));
+
+    assert!(repaint(&mut platform, cursor_moved(50.0, 50.0)));
+    assert!(repaint(&mut platform, mouse_input(ElementState::Released)));
+    assert!(repaint(
+        &mut platform,
+        WindowEvent::Resized((640, 480).into())
+    ));
+    assert!(!repaint(&mut platform, WindowEvent::Focused(true)));
+    assert!(!repaint(&mut platform, WindowEvent::Moved((10, 20).into())));
+}
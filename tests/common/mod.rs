@@ -0,0 +1,59 @@
+//! Fixtures shared by the integration tests.
+
+#![allow(dead_code)]
+
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{DeviceId, ElementState, MouseButton, WindowEvent},
+};
+
+/// Returns the descriptor of an 800x600 window with a scale factor of 1.
+pub fn descriptor() -> PlatformDescriptor {
+    PlatformDescriptor {
+        physical_width: 800,
+        physical_height: 600,
+        scale_factor: 1.0,
+        ..Default::default()
+    }
+}
+
+/// Returns a platform for an 800x600 window with a scale factor of 1.
+pub fn platform() -> Platform {
+    Platform::new(descriptor())
+}
+
+/// Runs a frame with the given UI without painting it.
+pub fn frame(platform: &mut Platform, ui: impl FnOnce(&egui::Context)) -> egui::FullOutput {
+    platform.begin_pass();
+    ui(&platform.context());
+    platform.end_pass(None)
+}
+
+pub fn cursor_moved(x: f64, y: f64) -> WindowEvent {
+    WindowEvent::CursorMoved {
+        device_id: DeviceId::dummy(),
+        position: PhysicalPosition::new(x, y),
+    }
+}
+
+pub fn mouse_input(state: ElementState) -> WindowEvent {
+    WindowEvent::MouseInput {
+        device_id: DeviceId::dummy(),
+        state,
+        button: MouseButton::Left,
+    }
+}
+
+pub fn move_to(platform: &mut Platform, x: f64, y: f64) {
+    platform.handle_event(&cursor_moved(x, y));
+}
+
+pub fn left_button(platform: &mut Platform, state: ElementState) {
+    platform.handle_event(&mouse_input(state));
+}
+
+/// Returns the events that egui will receive in the next frame.
+pub fn take_events(platform: &mut Platform) -> Vec<egui::Event> {
+    std::mem::take(&mut platform.raw_input_mut().events)
+}
@@ -0,0 +1,77 @@
+use egui::{Color32, LayerId, Pos2, Rect};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+
+mod common;
+
+fn platform(damage_tracking: bool) -> Platform {
+    Platform::new(PlatformDescriptor {
+        physical_width: 400,
+        physical_height: 300,
+        scale_factor: 2.0,
+        damage_tracking,
+        ..common::descriptor()
+    })
+}
+
+/// Paints two rectangles, the second one at `x` points.
+fn frame(platform: &mut Platform, x: f32) {
+    common::frame(platform, |ctx| {
+        let painter = ctx.layer_painter(LayerId::background());
+        painter.rect_filled(
+            Rect::from_min_max(Pos2::new(10.0, 10.0), Pos2::new(20.0, 20.0)),
+            0.0,
+            Color32::RED,
+        );
+        painter.rect_filled(
+            Rect::from_min_max(Pos2::new(x, 100.0), Pos2::new(x + 10.0, 110.0)),
+            0.0,
+            Color32::BLUE,
+        );
+    });
+}
+
+#[test]
+fn first_frame_damages_the_screen() {
+    let mut platform = platform(true);
+    frame(&mut platform, 50.0);
+
+    assert_eq!(
+        platform.damage_regions().unwrap(),
+        [Rect::from_min_max(Pos2::ZERO, Pos2::new(400.0, 300.0))]
+    );
+}
+
+#[test]
+fn unchanged_frame_has_no_damage() {
+    let mut platform = platform(true);
+    frame(&mut platform, 50.0);
+    frame(&mut platform, 50.0);
+
+    assert_eq!(platform.damage_regions().unwrap(), []);
+}
+
+#[test]
+fn moved_shape_damages_old_and_new_area() {
+    let mut platform = platform(true);
+    frame(&mut platform, 50.0);
+    frame(&mut platform, 50.0);
+    frame(&mut platform, 150.0);
+
+    let regions = platform.damage_regions().unwrap();
+    assert_eq!(regions.len(), 2);
+    for point in [Pos2::new(110.0, 210.0), Pos2::new(310.0, 210.0)] {
+        assert!(regions.iter().any(|region| region.contains(point)));
+    }
+    // The unchanged rectangle isn't damaged.
+    assert!(!regions
+        .iter()
+        .any(|region| region.contains(Pos2::new(30.0, 30.0))));
+}
+
+#[test]
+fn disabled_damage_tracking() {
+    let mut platform = platform(false);
+    frame(&mut platform, 50.0);
+
+    assert!(platform.damage_regions().is_none());
+}
@@ -0,0 +1,91 @@
+use egui::epaint::Primitive;
+use egui::{Color32, LayerId, Rect};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+
+mod common;
+
+fn platform() -> Platform {
+    Platform::new(PlatformDescriptor {
+        physical_width: 200,
+        physical_height: 100,
+        scale_factor: 2.0,
+        ..common::descriptor()
+    })
+}
+
+fn paint(platform: &mut Platform) {
+    platform.begin_pass();
+    platform
+        .context()
+        .layer_painter(LayerId::background())
+        .rect_filled(
+            Rect::from_min_max([10.0, 10.0].into(), [30.0, 30.0].into()),
+            0.0,
+            Color32::RED,
+        );
+    // Small circles are painted with the discs that are prerasterized into the font atlas,
+    // which grows with the glyphs of the text.
+    let painter = platform.context().layer_painter(LayerId::background());
+    painter.circle_filled([50.0, 20.0].into(), 2.0, Color32::BLUE);
+    painter.text(
+        [10.0, 40.0].into(),
+        egui::Align2::LEFT_TOP,
+        "The quick brown fox jumps over the lazy dog 0123456789",
+        egui::FontId::proportional(40.0),
+        Color32::WHITE,
+    );
+}
+
+#[test]
+fn frame_is_tessellated_at_the_scale_factor() {
+    let mut platform = platform();
+    paint(&mut platform);
+    let frame = platform.end_pass_tessellated(None);
+
+    assert_eq!(frame.pixels_per_point, 2.0);
+    assert_eq!(frame.screen_size_in_pixels, [200, 100]);
+    assert_eq!(frame.primitives.len(), 1);
+    assert!(!frame.textures_delta.set.is_empty());
+}
+
+#[test]
+fn async_tessellation_matches() {
+    let mut platform = platform();
+    paint(&mut platform);
+    let expected = platform.end_pass_tessellated(None);
+    paint(&mut platform);
+    let frame = platform.end_pass_tessellate_async(None).wait();
+
+    assert_eq!(frame.pixels_per_point, expected.pixels_per_point);
+    assert_eq!(frame.screen_size_in_pixels, expected.screen_size_in_pixels);
+    assert_eq!(frame.primitives.len(), expected.primitives.len());
+    for (primitive, expected) in frame.primitives.iter().zip(&expected.primitives) {
+        assert_eq!(primitive.clip_rect, expected.clip_rect);
+        match (&primitive.primitive, &expected.primitive) {
+            (Primitive::Mesh(mesh), Primitive::Mesh(expected)) => assert_eq!(mesh, expected),
+            _ => panic!("expected meshes"),
+        }
+    }
+}
+
+#[test]
+fn input_is_handled_during_async_tessellation() {
+    let mut platform = platform();
+    platform.begin_pass();
+    let painter = platform.context().layer_painter(LayerId::background());
+    for i in 0..1000 {
+        let center = egui::pos2((i % 100) as f32, (i / 10) as f32);
+        painter.circle_stroke(center, 40.0, (1.5, Color32::RED));
+    }
+    let pending = platform.end_pass_tessellate_async(None);
+
+    // The platform isn't borrowed by the pending frame.
+    let response = platform.handle_event(&common::cursor_moved(10.0, 10.0));
+    assert!(response.repaint);
+    assert_eq!(
+        platform.raw_input_mut().events,
+        vec![egui::Event::PointerMoved(egui::pos2(5.0, 5.0))]
+    );
+
+    assert_eq!(pending.wait().primitives.len(), 1);
+}
@@ -0,0 +1,81 @@
+use egui::{Event, Key};
+use egui_winit_platform::{InputQueueFull, KeyInput, Platform};
+use winit::event::ElementState;
+use winit::keyboard::{KeyCode, KeyLocation, NamedKey, PhysicalKey};
+
+mod common;
+
+use common::platform;
+
+fn next_events(platform: &mut Platform) -> Vec<Event> {
+    platform.begin_pass();
+    let events = platform.context().input(|input| input.raw.events.clone());
+    platform.end_pass(None);
+    events
+}
+
+#[test]
+fn input_from_other_threads_is_merged_in_order() {
+    let mut platform = platform();
+    let sender = platform.input_sender();
+
+    std::thread::spawn(move || {
+        sender.send_event(Event::Text("a".to_owned())).unwrap();
+        sender
+            .send_key(KeyInput {
+                physical_key: PhysicalKey::Code(KeyCode::Enter),
+                logical_key: winit::keyboard::Key::Named(NamedKey::Enter),
+                text: None,
+                location: KeyLocation::Standard,
+                state: ElementState::Pressed,
+                repeat: false,
+            })
+            .unwrap();
+        sender
+            .send_dropped_file(egui::DroppedFile {
+                path: Some("dropped.txt".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        sender.send_event(Event::Text("b".to_owned())).unwrap();
+    })
+    .join()
+    .unwrap();
+    assert!(platform.context().has_requested_repaint());
+
+    platform.begin_pass();
+    let (events, dropped_files) = platform
+        .context()
+        .input(|input| (input.raw.events.clone(), input.raw.dropped_files.clone()));
+    platform.end_pass(None);
+
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0], Event::Text("a".to_owned()));
+    assert!(matches!(
+        events[1],
+        Event::Key {
+            key: Key::Enter,
+            pressed: true,
+            ..
+        }
+    ));
+    assert_eq!(events[2], Event::Text("b".to_owned()));
+    assert_eq!(dropped_files.len(), 1);
+    assert_eq!(dropped_files[0].path, Some("dropped.txt".into()));
+
+    assert!(next_events(&mut platform).is_empty());
+}
+
+#[test]
+fn full_queue_rejects_input() {
+    let mut platform = platform();
+    platform.set_input_queue_capacity(2);
+    let sender = platform.input_sender();
+
+    assert_eq!(sender.send_event(Event::Copy), Ok(()));
+    assert_eq!(sender.clone().send_event(Event::Cut), Ok(()));
+    assert_eq!(sender.send_event(Event::Copy), Err(InputQueueFull));
+
+    assert_eq!(next_events(&mut platform), vec![Event::Copy, Event::Cut]);
+    assert_eq!(sender.send_event(Event::Copy), Ok(()));
+}
@@ -0,0 +1,398 @@
+use egui::Event;
+use egui_winit_platform::{
+    KeyAction, KeyChord, KeyInput, ModifierKeys, Platform, PlatformDescriptor,
+};
+use winit::{
+    event::{DeviceId, ElementState, Modifiers, MouseButton, WindowEvent},
+    keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NamedKey, PhysicalKey},
+};
+
+mod common;
+
+fn platform(text_filter: Option<fn(char) -> bool>) -> Platform {
+    Platform::new(PlatformDescriptor {
+        text_filter,
+        ..common::descriptor()
+    })
+}
+
+fn set_modifiers(platform: &mut Platform, modifiers: ModifiersState) {
+    platform.handle_event(&WindowEvent::ModifiersChanged(Modifiers::from(modifiers)));
+}
+
+/// Presses the key and returns the text egui receives.
+fn type_key(platform: &mut Platform, code: KeyCode, text: &str) -> Vec<String> {
+    platform.handle_key_input(&KeyInput {
+        physical_key: PhysicalKey::Code(code),
+        logical_key: Key::Character(text.into()),
+        text: Some(text.into()),
+        location: KeyLocation::Standard,
+        state: ElementState::Pressed,
+        repeat: false,
+    });
+    platform
+        .raw_input_mut()
+        .events
+        .drain(..)
+        .filter_map(|event| match event {
+            Event::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Windows reports AltGr as Ctrl+Alt.
+const ALT_GR: ModifiersState = ModifiersState::CONTROL.union(ModifiersState::ALT);
+
+#[test]
+fn alt_gr_on_polish_layout() {
+    let mut platform = platform(None);
+    set_modifiers(&mut platform, ALT_GR);
+    assert_eq!(type_key(&mut platform, KeyCode::KeyL, "ł"), vec!["ł"]);
+}
+
+#[test]
+fn alt_gr_on_german_layout() {
+    let mut platform = platform(None);
+    set_modifiers(&mut platform, ALT_GR);
+    assert_eq!(type_key(&mut platform, KeyCode::KeyQ, "@"), vec!["@"]);
+    assert_eq!(type_key(&mut platform, KeyCode::KeyE, "€"), vec!["€"]);
+}
+
+#[test]
+fn alt_gr_on_french_layout() {
+    let mut platform = platform(None);
+    set_modifiers(&mut platform, ALT_GR);
+    assert_eq!(type_key(&mut platform, KeyCode::Digit0, "@"), vec!["@"]);
+}
+
+#[test]
+fn shortcuts_produce_no_text() {
+    let mut platform = platform(None);
+    set_modifiers(&mut platform, ModifiersState::CONTROL);
+    assert!(type_key(&mut platform, KeyCode::KeyS, "s").is_empty());
+
+    set_modifiers(&mut platform, ModifiersState::SUPER | ALT_GR);
+    assert!(type_key(&mut platform, KeyCode::KeyS, "s").is_empty());
+}
+
+#[test]
+fn default_filter_drops_private_use_characters() {
+    let mut platform = platform(None);
+    assert!(type_key(&mut platform, KeyCode::F1, "\u{f704}").is_empty());
+    assert_eq!(type_key(&mut platform, KeyCode::KeyA, "a"), vec!["a"]);
+}
+
+#[test]
+fn custom_text_filter() {
+    let mut platform = platform(Some(|c| !c.is_ascii_control()));
+    assert_eq!(
+        type_key(&mut platform, KeyCode::F1, "\u{f704}"),
+        vec!["\u{f704}"]
+    );
+
+    platform.set_text_filter(|c| c.is_ascii_digit());
+    assert!(type_key(&mut platform, KeyCode::KeyA, "a").is_empty());
+    assert_eq!(type_key(&mut platform, KeyCode::Digit1, "1"), vec!["1"]);
+}
+
+fn modifier_key(platform: &mut Platform, code: KeyCode, key: NamedKey, state: ElementState) {
+    let location = match code {
+        KeyCode::ShiftRight | KeyCode::ControlRight | KeyCode::AltRight | KeyCode::SuperRight => {
+            KeyLocation::Right
+        }
+        _ => KeyLocation::Left,
+    };
+    platform.handle_key_input(&KeyInput {
+        physical_key: PhysicalKey::Code(code),
+        logical_key: Key::Named(key),
+        text: None,
+        location,
+        state,
+        repeat: false,
+    });
+}
+
+#[test]
+fn modifier_keys_keep_their_side() {
+    let mut platform = platform(None);
+    modifier_key(
+        &mut platform,
+        KeyCode::ShiftRight,
+        NamedKey::Shift,
+        ElementState::Pressed,
+    );
+    // The side is unknown in this event.
+    set_modifiers(&mut platform, ModifiersState::SHIFT);
+
+    assert_eq!(
+        platform.modifier_keys(),
+        ModifierKeys {
+            right_shift: true,
+            ..Default::default()
+        }
+    );
+    assert!(platform.raw_input_mut().modifiers.shift);
+
+    modifier_key(
+        &mut platform,
+        KeyCode::ShiftLeft,
+        NamedKey::Shift,
+        ElementState::Pressed,
+    );
+    modifier_key(
+        &mut platform,
+        KeyCode::ShiftRight,
+        NamedKey::Shift,
+        ElementState::Released,
+    );
+    assert_eq!(
+        platform.modifier_keys(),
+        ModifierKeys {
+            left_shift: true,
+            ..Default::default()
+        }
+    );
+
+    set_modifiers(&mut platform, ModifiersState::empty());
+    assert_eq!(platform.modifier_keys(), ModifierKeys::default());
+    assert!(!platform.raw_input_mut().modifiers.shift);
+}
+
+#[test]
+fn modifiers_recover_from_key_events() {
+    let mut platform = platform(None);
+    // No `ModifiersChanged` event is received for the control key.
+    modifier_key(
+        &mut platform,
+        KeyCode::ControlLeft,
+        NamedKey::Control,
+        ElementState::Pressed,
+    );
+    assert!(platform.raw_input_mut().modifiers.ctrl);
+    assert!(type_key(&mut platform, KeyCode::KeyS, "s").is_empty());
+
+    modifier_key(
+        &mut platform,
+        KeyCode::ControlLeft,
+        NamedKey::Control,
+        ElementState::Released,
+    );
+    assert!(!platform.raw_input_mut().modifiers.ctrl);
+    assert_eq!(type_key(&mut platform, KeyCode::KeyS, "s"), vec!["s"]);
+}
+
+#[test]
+fn focus_loss_releases_modifiers() {
+    let mut platform = platform(None);
+    modifier_key(
+        &mut platform,
+        KeyCode::AltLeft,
+        NamedKey::Alt,
+        ElementState::Pressed,
+    );
+    platform.handle_event(&WindowEvent::Focused(false));
+
+    assert_eq!(platform.modifier_keys(), ModifierKeys::default());
+    assert!(!platform.raw_input_mut().modifiers.alt);
+}
+
+#[test]
+fn focus_loss_releases_mouse_buttons() {
+    let mut platform = platform(None);
+    common::move_to(&mut platform, 10.0, 20.0);
+    common::left_button(&mut platform, ElementState::Pressed);
+    platform.handle_event(&WindowEvent::CursorLeft {
+        device_id: DeviceId::dummy(),
+    });
+    platform.raw_input_mut().events.clear();
+
+    // The release happens in another window, so it's never reported.
+    platform.handle_event(&WindowEvent::Focused(false));
+    assert_eq!(
+        platform.raw_input_mut().events,
+        vec![
+            Event::PointerButton {
+                pos: egui::pos2(10.0, 20.0),
+                button: egui::PointerButton::Primary,
+                pressed: false,
+                modifiers: Default::default(),
+            },
+            Event::PointerGone,
+        ]
+    );
+    assert!(!platform.app_input().is_mouse_button_down(MouseButton::Left));
+}
+
+#[test]
+fn key_event_replaces_guessed_side() {
+    let mut platform = platform(None);
+    // Windows sends `ModifiersChanged` without the side before the key event.
+    set_modifiers(&mut platform, ModifiersState::SHIFT);
+    modifier_key(
+        &mut platform,
+        KeyCode::ShiftRight,
+        NamedKey::Shift,
+        ElementState::Pressed,
+    );
+    assert_eq!(
+        platform.modifier_keys(),
+        ModifierKeys {
+            right_shift: true,
+            ..Default::default()
+        }
+    );
+
+    set_modifiers(&mut platform, ModifiersState::empty());
+    modifier_key(
+        &mut platform,
+        KeyCode::ShiftRight,
+        NamedKey::Shift,
+        ElementState::Released,
+    );
+    assert_eq!(platform.modifier_keys(), ModifierKeys::default());
+
+    // A held left key isn't replaced by the right one.
+    set_modifiers(&mut platform, ModifiersState::CONTROL);
+    modifier_key(
+        &mut platform,
+        KeyCode::ControlLeft,
+        NamedKey::Control,
+        ElementState::Pressed,
+    );
+    modifier_key(
+        &mut platform,
+        KeyCode::ControlRight,
+        NamedKey::Control,
+        ElementState::Pressed,
+    );
+    assert_eq!(
+        platform.modifier_keys(),
+        ModifierKeys {
+            left_control: true,
+            right_control: true,
+            ..Default::default()
+        }
+    );
+}
+
+/// Presses or releases a character key and returns the events egui receives.
+fn character_key(
+    platform: &mut Platform,
+    code: KeyCode,
+    text: &str,
+    state: ElementState,
+) -> Vec<Event> {
+    platform.handle_key_input(&KeyInput {
+        physical_key: PhysicalKey::Code(code),
+        logical_key: Key::Character(text.into()),
+        text: Some(text.into()),
+        location: KeyLocation::Standard,
+        state,
+        repeat: false,
+    });
+    platform.raw_input_mut().events.drain(..).collect()
+}
+
+fn key_event(
+    key: egui::Key,
+    physical_key: Option<egui::Key>,
+    pressed: bool,
+    modifiers: egui::Modifiers,
+) -> Event {
+    Event::Key {
+        key,
+        physical_key,
+        pressed,
+        repeat: false,
+        modifiers,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn copy_sends_the_key_and_copy() {
+    let mut platform = platform(None);
+    set_modifiers(&mut platform, ModifiersState::CONTROL);
+
+    let c = Some(egui::Key::C);
+    assert_eq!(
+        character_key(&mut platform, KeyCode::KeyC, "c", ElementState::Pressed),
+        vec![
+            key_event(egui::Key::C, c, true, egui::Modifiers::CTRL),
+            Event::Copy
+        ]
+    );
+    assert_eq!(
+        character_key(&mut platform, KeyCode::KeyC, "c", ElementState::Released),
+        vec![key_event(egui::Key::C, c, false, egui::Modifiers::CTRL)]
+    );
+}
+
+#[cfg(all(feature = "clipboard", not(target_os = "macos")))]
+#[test]
+fn paste_sends_the_clipboard_text() {
+    struct Clipboard(String);
+
+    impl copypasta::ClipboardProvider for Clipboard {
+        fn get_contents(&mut self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(self.0.clone())
+        }
+
+        fn set_contents(
+            &mut self,
+            contents: String,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.0 = contents;
+            Ok(())
+        }
+    }
+
+    let mut platform = platform(None);
+    platform.set_clipboard(Some(Box::new(Clipboard("pasted".to_owned()))));
+    set_modifiers(&mut platform, ModifiersState::CONTROL);
+
+    let v = Some(egui::Key::V);
+    assert_eq!(
+        character_key(&mut platform, KeyCode::KeyV, "v", ElementState::Pressed),
+        vec![
+            key_event(egui::Key::V, v, true, egui::Modifiers::CTRL),
+            Event::Paste("pasted".to_owned())
+        ]
+    );
+    assert_eq!(
+        character_key(&mut platform, KeyCode::KeyV, "v", ElementState::Released),
+        vec![key_event(egui::Key::V, v, false, egui::Modifiers::CTRL)]
+    );
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn release_of_a_replaced_key_is_suppressed() {
+    let mut platform = platform(None);
+    platform.keymap_mut().bind(
+        KeyChord::logical(ModifiersState::CONTROL, egui::Key::Y),
+        KeyAction::Redo,
+    );
+    set_modifiers(&mut platform, ModifiersState::CONTROL);
+
+    // Egui receives the redo shortcut in place of the key.
+    let redo = egui::Modifiers::CTRL | egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+    assert_eq!(
+        character_key(&mut platform, KeyCode::KeyY, "y", ElementState::Pressed),
+        vec![
+            key_event(egui::Key::Z, None, true, redo),
+            key_event(egui::Key::Z, None, false, redo)
+        ]
+    );
+    assert!(character_key(&mut platform, KeyCode::KeyY, "y", ElementState::Released).is_empty());
+
+    // Without the chord the key is sent and released as usual.
+    set_modifiers(&mut platform, ModifiersState::empty());
+    let y = Some(egui::Key::Y);
+    character_key(&mut platform, KeyCode::KeyY, "y", ElementState::Pressed);
+    assert_eq!(
+        character_key(&mut platform, KeyCode::KeyY, "y", ElementState::Released),
+        vec![key_event(egui::Key::Y, y, false, egui::Modifiers::NONE)]
+    );
+}
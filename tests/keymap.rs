@@ -0,0 +1,174 @@
+use egui::{Event, Key, Modifiers};
+use egui_winit_platform::{
+    KeyAction, KeyChord, KeyInput, Keymap, KeymapError, Platform, PlatformDescriptor,
+};
+use winit::{
+    event::{ElementState, WindowEvent},
+    keyboard::{KeyCode, KeyLocation, ModifiersState, PhysicalKey},
+};
+
+mod common;
+
+#[test]
+fn parse_keymap() {
+    let keymap = Keymap::parse(
+        "
+        # Shortcuts of the editor
+        Ctrl+C = copy
+        Ctrl+Shift+Z = redo
+        Alt+Physical(S) = user save
+        Ctrl++ = user zoom in
+        ",
+    )
+    .unwrap();
+
+    let bindings: Vec<_> = keymap.bindings().collect();
+    assert_eq!(
+        bindings,
+        vec![
+            (
+                &KeyChord::logical(ModifiersState::CONTROL, Key::C),
+                &KeyAction::Copy
+            ),
+            (
+                &KeyChord::logical(ModifiersState::CONTROL | ModifiersState::SHIFT, Key::Z),
+                &KeyAction::Redo
+            ),
+            (
+                &KeyChord::physical(ModifiersState::ALT, Key::S),
+                &KeyAction::User("save".to_owned())
+            ),
+            (
+                &KeyChord::logical(ModifiersState::CONTROL, Key::Plus),
+                &KeyAction::User("zoom in".to_owned())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn parse_errors_name_the_line() {
+    let err = Keymap::parse("Ctrl+C = copy\nHyper+C = copy\n").unwrap_err();
+    assert!(matches!(err, KeymapError::Parse { line: 2, .. }));
+
+    let err = Keymap::parse("Ctrl+C = launch").unwrap_err();
+    assert!(matches!(err, KeymapError::Parse { line: 1, .. }));
+}
+
+#[test]
+fn match_physical_and_logical_keys() {
+    let keymap = Keymap::parse("Ctrl+Physical(Z) = undo\nShift+Insert = paste").unwrap();
+
+    // The Z key of a German keyboard is at the position of the US Y key.
+    let german_z = keymap.action(
+        &winit::keyboard::Key::Character("z".into()),
+        PhysicalKey::Code(KeyCode::KeyY),
+        ModifiersState::CONTROL,
+    );
+    let us_z = keymap.action(
+        &winit::keyboard::Key::Character("y".into()),
+        PhysicalKey::Code(KeyCode::KeyZ),
+        ModifiersState::CONTROL,
+    );
+    assert_eq!(german_z, None);
+    assert_eq!(us_z, Some(&KeyAction::Undo));
+
+    let paste = keymap.action(
+        &winit::keyboard::Key::Named(winit::keyboard::NamedKey::Insert),
+        PhysicalKey::Code(KeyCode::Insert),
+        ModifiersState::SHIFT,
+    );
+    let extra_modifier = keymap.action(
+        &winit::keyboard::Key::Named(winit::keyboard::NamedKey::Insert),
+        PhysicalKey::Code(KeyCode::Insert),
+        ModifiersState::SHIFT | ModifiersState::ALT,
+    );
+    assert_eq!(paste, Some(&KeyAction::Paste));
+    assert_eq!(extra_modifier, None);
+}
+
+fn platform(keymap: &str) -> Platform {
+    Platform::new(PlatformDescriptor {
+        keymap: Keymap::parse(keymap).unwrap(),
+        ..common::descriptor()
+    })
+}
+
+/// Presses and releases the key with the modifiers held and returns the events egui receives.
+fn press_chord(
+    platform: &mut Platform,
+    modifiers: ModifiersState,
+    code: KeyCode,
+    text: &str,
+) -> Vec<Event> {
+    platform.handle_event(&WindowEvent::ModifiersChanged(modifiers.into()));
+    for state in [ElementState::Pressed, ElementState::Released] {
+        platform.handle_key_input(&KeyInput {
+            physical_key: PhysicalKey::Code(code),
+            logical_key: winit::keyboard::Key::Character(text.into()),
+            text: Some(text.into()),
+            location: KeyLocation::Standard,
+            state,
+            repeat: false,
+        });
+    }
+    platform.handle_event(&WindowEvent::ModifiersChanged(
+        ModifiersState::empty().into(),
+    ));
+    platform.raw_input_mut().events.drain(..).collect()
+}
+
+fn keys(events: &[Event]) -> Vec<(Key, bool, Modifiers)> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Key {
+                key,
+                pressed,
+                modifiers,
+                ..
+            } => Some((*key, *pressed, *modifiers)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn user_actions_are_triggered_instead_of_the_key() {
+    let mut platform = platform("Ctrl+S = user save\nAlt+Physical(S) = user save as");
+
+    let events = press_chord(&mut platform, ModifiersState::CONTROL, KeyCode::KeyS, "s");
+    assert!(keys(&events).is_empty());
+    let events = press_chord(&mut platform, ModifiersState::ALT, KeyCode::KeyS, "ß");
+    assert!(keys(&events).is_empty());
+    assert_eq!(platform.take_triggered_actions(), vec!["save", "save as"]);
+    assert!(platform.take_triggered_actions().is_empty());
+
+    // Other modifiers don't match the chord, so egui receives the key.
+    let events = press_chord(&mut platform, ModifiersState::SUPER, KeyCode::KeyS, "s");
+    assert_eq!(keys(&events).len(), 2);
+    assert!(platform.take_triggered_actions().is_empty());
+}
+
+#[test]
+fn undo_and_redo_send_the_egui_shortcuts() {
+    let mut platform = platform("Ctrl+U = undo\nCtrl+R = redo");
+    let command = Modifiers::COMMAND
+        | if cfg!(target_os = "macos") {
+            Modifiers::MAC_CMD
+        } else {
+            Modifiers::CTRL
+        };
+
+    let events = press_chord(&mut platform, ModifiersState::CONTROL, KeyCode::KeyU, "u");
+    assert_eq!(
+        keys(&events),
+        vec![(Key::Z, true, command), (Key::Z, false, command)]
+    );
+    let events = press_chord(&mut platform, ModifiersState::CONTROL, KeyCode::KeyR, "r");
+    let redo = command | Modifiers::SHIFT;
+    assert_eq!(
+        keys(&events),
+        vec![(Key::Z, true, redo), (Key::Z, false, redo)]
+    );
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "persistence")]
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use egui::Id;
+use egui_winit_platform::{Storage, WindowGeometry};
+use winit::{dpi::PhysicalPosition, event::WindowEvent};
+
+mod common;
+
+use common::platform;
+
+fn storage_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("egui_winit_platform_{}", std::process::id()))
+        .join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn memory_and_values_are_restored() {
+    let path = storage_path("restore.ron");
+
+    let mut platform = platform();
+    platform.set_storage(Storage::load(&path));
+    platform
+        .context()
+        .data_mut(|data| data.insert_persisted(Id::new("counter"), 42u32));
+    platform
+        .storage_mut()
+        .unwrap()
+        .set_value("app", &vec![1, 2, 3]);
+    platform.save().unwrap();
+
+    let mut platform = self::platform();
+    platform.set_storage(Storage::load(&path));
+    let counter = platform
+        .context()
+        .data_mut(|data| data.get_persisted::<u32>(Id::new("counter")));
+    assert_eq!(counter, Some(42));
+    assert_eq!(
+        platform.storage().unwrap().get_value::<Vec<i32>>("app"),
+        Some(vec![1, 2, 3])
+    );
+}
+
+#[test]
+fn end_pass_autosaves() {
+    let path = storage_path("autosave.ron");
+
+    let mut platform = platform();
+    platform.set_storage(Storage::load(&path).with_autosave_interval(Duration::ZERO));
+    platform.storage_mut().unwrap().set_string("name", "value");
+    assert!(!path.exists());
+
+    platform.begin_pass();
+    platform.end_pass(None);
+
+    assert_eq!(Storage::load(&path).get_string("name"), Some("value"));
+}
+
+#[test]
+fn broken_file_starts_empty() {
+    let path = storage_path("broken.ron");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "not ron").unwrap();
+
+    assert_eq!(Storage::load(&path).get_string("name"), None);
+}
+
+#[test]
+fn window_geometry_is_saved() {
+    let path = storage_path("window.ron");
+
+    let mut platform = platform();
+    platform.set_storage(Storage::load(&path));
+    platform.handle_event(&WindowEvent::Moved(PhysicalPosition::new(100, 50)));
+    platform.save().unwrap();
+
+    assert_eq!(
+        WindowGeometry::load(&Storage::load(&path)),
+        Some(WindowGeometry {
+            position: Some([100, 50]),
+            inner_size: [800, 600],
+            ..Default::default()
+        })
+    );
+}
@@ -0,0 +1,76 @@
+use egui::{Event, PointerButton, Pos2};
+use egui_winit_platform::Platform;
+use winit::event::{DeviceId, ElementState, WindowEvent};
+
+mod common;
+
+use common::{left_button, move_to, platform, take_events};
+
+fn cursor_left(platform: &mut Platform) {
+    platform.handle_event(&WindowEvent::CursorLeft {
+        device_id: DeviceId::dummy(),
+    });
+}
+
+fn button(pos: Pos2, pressed: bool) -> Event {
+    Event::PointerButton {
+        pos,
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    }
+}
+
+#[test]
+fn press_outside_the_window_is_ignored() {
+    let mut platform = platform();
+    move_to(&mut platform, 10.0, 20.0);
+    cursor_left(&mut platform);
+    take_events(&mut platform);
+
+    // The press can't have happened inside the window, so the last position doesn't apply.
+    left_button(&mut platform, ElementState::Pressed);
+    left_button(&mut platform, ElementState::Released);
+    assert!(take_events(&mut platform).is_empty());
+}
+
+#[test]
+fn release_outside_the_window_ends_the_drag() {
+    let mut platform = platform();
+    move_to(&mut platform, 10.0, 20.0);
+    left_button(&mut platform, ElementState::Pressed);
+    move_to(&mut platform, 30.0, 40.0);
+    cursor_left(&mut platform);
+    take_events(&mut platform);
+
+    left_button(&mut platform, ElementState::Released);
+    assert_eq!(
+        take_events(&mut platform),
+        vec![button(Pos2::new(30.0, 40.0), false), Event::PointerGone]
+    );
+}
+
+#[test]
+fn pointer_gone_is_delayed_until_the_drag_ends() {
+    let mut platform = platform();
+    move_to(&mut platform, 10.0, 20.0);
+    left_button(&mut platform, ElementState::Pressed);
+    take_events(&mut platform);
+
+    cursor_left(&mut platform);
+    assert!(take_events(&mut platform).is_empty());
+
+    // The drag continues inside the window again, so the pointer isn't gone.
+    platform.handle_event(&WindowEvent::CursorEntered {
+        device_id: DeviceId::dummy(),
+    });
+    move_to(&mut platform, 50.0, 60.0);
+    left_button(&mut platform, ElementState::Released);
+    assert_eq!(
+        take_events(&mut platform),
+        vec![
+            Event::PointerMoved(Pos2::new(50.0, 60.0)),
+            button(Pos2::new(50.0, 60.0), false),
+        ]
+    );
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "runner")]
+
+use std::time::Duration;
+
+use egui::{Context, UserData, ViewportCommand};
+use egui_winit_platform::{App, HeadlessRenderer, PlatformDescriptor, Runner};
+
+#[derive(Default)]
+struct Counter {
+    updates: usize,
+    screenshots: usize,
+    animate: bool,
+}
+
+impl App for Counter {
+    fn update(&mut self, context: &Context) {
+        self.updates += 1;
+        self.screenshots += context.input(|input| {
+            input
+                .events
+                .iter()
+                .filter(|event| matches!(event, egui::Event::Screenshot { .. }))
+                .count()
+        });
+        egui::CentralPanel::default().show(context, |ui| {
+            ui.label("Hello");
+        });
+        if self.updates == 1 {
+            context.send_viewport_cmd(ViewportCommand::Screenshot(UserData::default()));
+        }
+        if self.animate {
+            context.request_repaint();
+        }
+    }
+}
+
+fn runner(app: Counter) -> Runner<Counter, HeadlessRenderer> {
+    Runner::headless(
+        app,
+        HeadlessRenderer::default(),
+        PlatformDescriptor {
+            physical_width: 200,
+            physical_height: 100,
+            scale_factor: 2.0,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn headless_frames_are_rendered() {
+    let mut runner = runner(Counter::default());
+    runner.step();
+    runner.step();
+
+    assert_eq!(runner.app().updates, 2);
+    assert_eq!(runner.renderer().frames, 2);
+    let frame = runner.renderer().last_frame.as_ref().unwrap();
+    assert_eq!(frame.screen_size_in_pixels, [200, 100]);
+    assert!(!frame.primitives.is_empty());
+}
+
+#[test]
+fn screenshots_are_delivered_to_the_app() {
+    let mut runner = runner(Counter::default());
+    let repaint_delay = runner.step();
+
+    assert_eq!(repaint_delay, Duration::ZERO);
+    runner.step();
+    assert_eq!(runner.app().screenshots, 1);
+}
+
+#[test]
+fn repaint_delay_follows_requests() {
+    let mut runner = runner(Counter {
+        animate: true,
+        ..Default::default()
+    });
+    runner.step();
+    runner.step();
+    assert_eq!(runner.step(), Duration::ZERO);
+}
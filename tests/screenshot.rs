@@ -0,0 +1,49 @@
+use egui::{Color32, ColorImage, Event, UserData, ViewportCommand};
+use egui_winit_platform::{Platform, PlatformDescriptor};
+
+#[test]
+fn screenshot_is_delivered_in_the_next_frame() {
+    let mut platform = Platform::new(PlatformDescriptor {
+        physical_width: 80,
+        physical_height: 60,
+        scale_factor: 1.0,
+        ..Default::default()
+    });
+
+    platform.begin_pass();
+    platform
+        .context()
+        .send_viewport_cmd(ViewportCommand::Screenshot(UserData::new("export")));
+    platform.end_pass(None);
+
+    let requests = platform.take_screenshot_requests();
+    assert_eq!(requests.len(), 1);
+    assert!(platform.take_screenshot_requests().is_empty());
+
+    let image = ColorImage::new([80, 60], vec![Color32::RED; 80 * 60]);
+    for user_data in requests {
+        platform.deliver_screenshot(image.clone(), user_data);
+    }
+    assert!(platform.context().has_requested_repaint());
+
+    platform.begin_pass();
+    let screenshots: Vec<_> = platform.context().input(|input| {
+        input
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Screenshot {
+                    user_data, image, ..
+                } => Some((user_data.clone(), image.clone())),
+                _ => None,
+            })
+            .collect()
+    });
+    platform.end_pass(None);
+
+    assert_eq!(screenshots.len(), 1);
+    let (user_data, screenshot) = &screenshots[0];
+    let tag = user_data.data.as_ref().unwrap().downcast_ref::<&str>();
+    assert_eq!(tag, Some(&"export"));
+    assert_eq!(**screenshot, image);
+}
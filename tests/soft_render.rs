@@ -0,0 +1,89 @@
+#![cfg(feature = "soft_render")]
+
+use egui::{Align2, Color32, ColorImage, FontId, LayerId, Rect};
+use egui_winit_platform::{Platform, PlatformDescriptor, SoftRenderer};
+
+mod common;
+
+fn platform() -> Platform {
+    Platform::new(PlatformDescriptor {
+        physical_width: 64,
+        physical_height: 48,
+        ..common::descriptor()
+    })
+}
+
+fn render(
+    platform: &mut Platform,
+    renderer: &mut SoftRenderer,
+    ui: impl FnOnce(&egui::Context),
+) -> ColorImage {
+    let output = common::frame(platform, ui);
+    renderer.render(&platform.context(), &output)
+}
+
+fn pixel(image: &ColorImage, x: usize, y: usize) -> Color32 {
+    image.pixels[y * image.size[0] + x]
+}
+
+#[test]
+fn shapes_are_clipped() {
+    let mut platform = platform();
+    let mut renderer = SoftRenderer::new();
+    let image = render(&mut platform, &mut renderer, |ctx| {
+        let painter = ctx
+            .layer_painter(LayerId::background())
+            .with_clip_rect(Rect::from_min_max([0.0, 0.0].into(), [20.0, 20.0].into()));
+        painter.rect_filled(
+            Rect::from_min_max([10.0, 10.0].into(), [30.0, 30.0].into()),
+            0.0,
+            Color32::RED,
+        );
+    });
+
+    assert_eq!(image.size, [64, 48]);
+    assert_eq!(pixel(&image, 15, 15), Color32::RED);
+    assert_eq!(pixel(&image, 5, 5), Color32::TRANSPARENT);
+    assert_eq!(pixel(&image, 25, 25), Color32::TRANSPARENT);
+}
+
+#[test]
+fn colors_are_blended_with_premultiplied_alpha() {
+    let mut platform = platform();
+    let mut renderer = SoftRenderer::new().with_clear_color(Color32::BLACK);
+    let image = render(&mut platform, &mut renderer, |ctx| {
+        let painter = ctx.layer_painter(LayerId::background());
+        let rect = Rect::from_min_max([10.0, 10.0].into(), [30.0, 30.0].into());
+        painter.rect_filled(rect, 0.0, Color32::RED);
+        painter.rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(0, 0, 255, 128));
+    });
+
+    assert_eq!(pixel(&image, 5, 5), Color32::BLACK);
+    let [r, g, b, a] = pixel(&image, 20, 20).to_array();
+    assert!((126..=128).contains(&r), "red {}", r);
+    assert_eq!(g, 0);
+    assert!((127..=129).contains(&b), "blue {}", b);
+    assert_eq!(a, 255);
+}
+
+#[test]
+fn textures_are_kept_between_frames() {
+    let mut platform = platform();
+    let mut renderer = SoftRenderer::new();
+    let text = |ctx: &egui::Context| {
+        ctx.layer_painter(LayerId::background()).text(
+            [4.0, 4.0].into(),
+            Align2::LEFT_TOP,
+            "Hello",
+            FontId::default(),
+            Color32::WHITE,
+        );
+    };
+
+    // The font texture is only sent with the first frame.
+    let first = render(&mut platform, &mut renderer, text);
+    let second = render(&mut platform, &mut renderer, text);
+
+    assert!(first.pixels.iter().any(|pixel| pixel.a() > 0));
+    assert_eq!(first.pixels, second.pixels);
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "testing")]
+
+use egui::accesskit::Role;
+use egui::Context;
+use egui_winit_platform::{Platform, PlatformDescriptor};
+use winit::keyboard::{Key, NamedKey};
+
+mod common;
+
+#[derive(Default)]
+struct Form {
+    saved: usize,
+    remember: bool,
+    name: String,
+    submitted: Option<String>,
+}
+
+impl Form {
+    fn ui(&mut self, context: &Context) {
+        egui::CentralPanel::default().show(context, |ui| {
+            if ui.button("Save").clicked() {
+                self.saved += 1;
+            }
+            ui.checkbox(&mut self.remember, "Remember me");
+            let label = ui.label("Name");
+            let response = ui
+                .add(egui::TextEdit::singleline(&mut self.name))
+                .labelled_by(label.id);
+            if response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                self.submitted = Some(self.name.clone());
+            }
+            ui.add_enabled(!self.name.is_empty(), egui::Button::new("Submit"));
+        });
+    }
+}
+
+fn platform() -> Platform {
+    Platform::new(PlatformDescriptor {
+        scale_factor: 2.0,
+        ..common::descriptor()
+    })
+}
+
+#[test]
+fn click_widgets_by_label() {
+    let mut platform = platform();
+    let mut form = Form::default();
+    platform.run_until_settled(|context| form.ui(context));
+
+    platform.find_by_label("Save").assert_enabled().click();
+    platform.run_until_settled(|context| form.ui(context));
+    assert_eq!(form.saved, 1);
+
+    platform
+        .find_by_label("Remember me")
+        .assert_toggled(false)
+        .click();
+    platform.run_until_settled(|context| form.ui(context));
+    platform.find_by_label("Remember me").assert_toggled(true);
+    assert!(form.remember);
+}
+
+#[test]
+fn type_into_text_edit() {
+    let mut platform = platform();
+    let mut form = Form::default();
+    platform.run_until_settled(|context| form.ui(context));
+    platform.find_by_label("Submit").assert_disabled();
+
+    let edit = platform
+        .widgets()
+        .into_iter()
+        .find(|widget| widget.role == Role::TextInput)
+        .expect("text edit should be in the tree");
+    assert!(edit.rect.is_some());
+
+    platform.find_by_label("Save").click();
+    platform.run_until_settled(|context| form.ui(context));
+    assert!(platform
+        .widgets()
+        .iter()
+        .all(|widget| widget.role != Role::TextInput || !widget.focused));
+
+    platform
+        .query_by_label("Name")
+        .expect("text edit should be labelled")
+        .type_text("Ferris");
+    platform.run_until_settled(|context| form.ui(context));
+    platform
+        .find_by_label("Name")
+        .assert_focused()
+        .assert_value("Ferris");
+    platform.find_by_label("Submit").assert_enabled();
+
+    platform.press_key(Key::Named(NamedKey::Backspace));
+    platform.type_text("!");
+    platform.press_key(Key::Named(NamedKey::Enter));
+    platform.run_until_settled(|context| form.ui(context));
+    assert_eq!(form.submitted.as_deref(), Some("Ferri!"));
+}
+
+#[test]
+fn missing_widgets() {
+    let mut platform = platform();
+    let mut form = Form::default();
+    platform.run_until_settled(|context| form.ui(context));
+
+    assert!(platform.query_by_label("Delete").is_none());
+    platform.assert_no_label("Delete");
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        platform.find_by_label("Delete");
+    }))
+    .unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("\"Save\""), "{}", message);
+}
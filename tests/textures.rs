@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use egui::epaint::ImageDelta;
+use egui::{Color32, ColorImage, TextureId, TextureOptions, TexturesDelta};
+use egui_winit_platform::{TextureBackend, TextureManager};
+
+/// Keeps the textures in memory and records the calls of the manager.
+#[derive(Default)]
+struct MemoryBackend {
+    next_handle: usize,
+    log: Vec<String>,
+}
+
+struct MemoryTexture {
+    handle: usize,
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+    options: TextureOptions,
+}
+
+impl TextureBackend for MemoryBackend {
+    type Texture = MemoryTexture;
+
+    fn create_texture(&mut self, image: &ColorImage, options: TextureOptions) -> MemoryTexture {
+        self.next_handle += 1;
+        self.log.push(format!("create {}", self.next_handle));
+        MemoryTexture {
+            handle: self.next_handle,
+            size: image.size,
+            pixels: image.pixels.clone(),
+            options,
+        }
+    }
+
+    fn update_texture(&mut self, texture: &mut MemoryTexture, pos: [usize; 2], image: &ColorImage) {
+        self.log.push(format!("update {}", texture.handle));
+        for y in 0..image.size[1] {
+            for x in 0..image.size[0] {
+                texture.pixels[(pos[1] + y) * texture.size[0] + pos[0] + x] =
+                    image.pixels[y * image.size[0] + x];
+            }
+        }
+    }
+
+    fn set_texture_options(&mut self, texture: &mut MemoryTexture, options: TextureOptions) {
+        self.log.push(format!("options {}", texture.handle));
+        texture.options = options;
+    }
+
+    fn free_texture(&mut self, texture: MemoryTexture) {
+        self.log.push(format!("free {}", texture.handle));
+    }
+}
+
+fn image(size: [usize; 2], color: Color32) -> Arc<ColorImage> {
+    Arc::new(ColorImage::new(size, vec![color; size[0] * size[1]]))
+}
+
+fn full(size: [usize; 2], color: Color32, options: TextureOptions) -> ImageDelta {
+    ImageDelta::full(image(size, color), options)
+}
+
+fn partial(
+    pos: [usize; 2],
+    size: [usize; 2],
+    color: Color32,
+    options: TextureOptions,
+) -> ImageDelta {
+    ImageDelta::partial(pos, image(size, color), options)
+}
+
+fn take_log(manager: &mut TextureManager<MemoryBackend>) -> Vec<String> {
+    std::mem::take(&mut manager.backend_mut().log)
+}
+
+const ID: TextureId = TextureId::Managed(1);
+
+#[test]
+fn partial_updates_patch_the_texture() {
+    let mut manager = TextureManager::new(MemoryBackend::default());
+    manager.update(&TexturesDelta {
+        set: vec![
+            (ID, full([4, 3], Color32::RED, TextureOptions::LINEAR)),
+            (
+                ID,
+                partial([1, 1], [2, 2], Color32::BLUE, TextureOptions::LINEAR),
+            ),
+        ],
+        free: vec![],
+    });
+
+    assert_eq!(take_log(&mut manager), ["create 1", "update 1"]);
+    let texture = manager.get(ID).unwrap();
+    assert_eq!(texture.size, [4, 3]);
+    let pixels = &texture.texture.pixels;
+    assert_eq!(pixels[0], Color32::RED);
+    assert_eq!(pixels[4 + 1], Color32::BLUE);
+    assert_eq!(pixels[2 * 4 + 2], Color32::BLUE);
+    assert_eq!(pixels[2 * 4 + 3], Color32::RED);
+}
+
+#[test]
+fn frees_are_deferred_until_the_end_of_the_frame() {
+    let mut manager = TextureManager::new(MemoryBackend::default());
+    manager.update(&TexturesDelta {
+        set: vec![(ID, full([2, 2], Color32::RED, TextureOptions::LINEAR))],
+        free: vec![],
+    });
+    manager.end_frame();
+    manager.update(&TexturesDelta {
+        set: vec![],
+        free: vec![ID],
+    });
+
+    assert!(manager.get(ID).is_some());
+    manager.end_frame();
+    assert!(manager.get(ID).is_none());
+    assert_eq!(take_log(&mut manager), ["create 1", "free 1"]);
+}
+
+#[test]
+fn replaced_textures_are_freed() {
+    let mut manager = TextureManager::new(MemoryBackend::default());
+    manager.update(&TexturesDelta {
+        set: vec![
+            (ID, full([2, 2], Color32::RED, TextureOptions::LINEAR)),
+            (ID, full([8, 8], Color32::RED, TextureOptions::LINEAR)),
+        ],
+        free: vec![],
+    });
+
+    assert_eq!(take_log(&mut manager), ["create 1", "create 2", "free 1"]);
+    assert_eq!(manager.get(ID).unwrap().size, [8, 8]);
+}
+
+#[test]
+fn options_are_tracked() {
+    let mut manager = TextureManager::new(MemoryBackend::default());
+    manager.update(&TexturesDelta {
+        set: vec![
+            (ID, full([2, 2], Color32::RED, TextureOptions::LINEAR)),
+            (
+                ID,
+                partial([0, 0], [1, 1], Color32::BLUE, TextureOptions::LINEAR),
+            ),
+            (
+                ID,
+                partial([0, 0], [1, 1], Color32::BLUE, TextureOptions::NEAREST),
+            ),
+        ],
+        free: vec![],
+    });
+
+    assert_eq!(
+        take_log(&mut manager),
+        ["create 1", "update 1", "options 1", "update 1"]
+    );
+    assert_eq!(manager.get(ID).unwrap().options, TextureOptions::NEAREST);
+    assert_eq!(
+        manager.get(ID).unwrap().texture.options,
+        TextureOptions::NEAREST
+    );
+}
+
+#[test]
+fn invalid_updates_are_ignored() {
+    let mut manager = TextureManager::new(MemoryBackend::default());
+    manager.update(&TexturesDelta {
+        set: vec![
+            (
+                TextureId::Managed(2),
+                partial([0, 0], [1, 1], Color32::BLUE, TextureOptions::LINEAR),
+            ),
+            (ID, full([2, 2], Color32::RED, TextureOptions::LINEAR)),
+            (
+                ID,
+                partial([1, 1], [2, 2], Color32::BLUE, TextureOptions::LINEAR),
+            ),
+        ],
+        free: vec![],
+    });
+
+    assert_eq!(take_log(&mut manager), ["create 1"]);
+    assert_eq!(manager.ids().collect::<Vec<_>>(), [ID]);
+}
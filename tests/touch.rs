@@ -0,0 +1,251 @@
+use egui::{Event, PointerButton, Pos2};
+use egui_winit_platform::{
+    Platform, PlatformDescriptor, TouchGestureEmulation, TouchPointerEmulation,
+};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{DeviceId, Force, Touch, TouchPhase, WindowEvent},
+};
+
+mod common;
+
+fn platform(touch_pointer_emulation: TouchPointerEmulation) -> Platform {
+    Platform::new(PlatformDescriptor {
+        scale_factor: 2.0,
+        touch_pointer_emulation,
+        ..common::descriptor()
+    })
+}
+
+fn gesture_platform() -> Platform {
+    Platform::new(PlatformDescriptor {
+        scale_factor: 2.0,
+        touch_gesture_emulation: Some(TouchGestureEmulation::default()),
+        ..common::descriptor()
+    })
+}
+
+/// Runs a frame and returns the events egui received in it.
+fn frame_events(platform: &mut Platform) -> Vec<Event> {
+    platform.begin_pass();
+    let events = platform.context().input(|i| i.events.clone());
+    platform.end_pass(None);
+    events
+}
+
+fn touch(platform: &mut Platform, id: u64, phase: TouchPhase, x: f64, y: f64) {
+    platform.handle_event(&WindowEvent::Touch(Touch {
+        device_id: DeviceId::dummy(),
+        phase,
+        location: PhysicalPosition::new(x, y),
+        force: None,
+        id,
+    }));
+}
+
+/// Returns the pointer events, skipping the raw touch events.
+fn pointer_events(platform: &mut Platform) -> Vec<Event> {
+    platform
+        .raw_input_mut()
+        .events
+        .drain(..)
+        .filter(|event| !matches!(event, Event::Touch { .. }))
+        .collect()
+}
+
+fn button(x: f32, y: f32, pressed: bool) -> Event {
+    Event::PointerButton {
+        pos: Pos2::new(x, y),
+        button: PointerButton::Primary,
+        pressed,
+        modifiers: Default::default(),
+    }
+}
+
+#[test]
+fn touch_without_pressure_has_no_force() {
+    let mut platform = platform(TouchPointerEmulation::Merged);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    platform.handle_event(&WindowEvent::Touch(Touch {
+        device_id: DeviceId::dummy(),
+        phase: TouchPhase::Moved,
+        location: PhysicalPosition::new(20.0, 20.0),
+        force: Some(Force::Normalized(0.5)),
+        id: 0,
+    }));
+
+    let forces: Vec<_> = platform
+        .raw_input_mut()
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Touch { force, .. } => Some(*force),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(forces, vec![None, Some(0.5)]);
+}
+
+#[test]
+fn merged_pointer_stays_pressed_until_last_finger_lifts() {
+    let mut platform = platform(TouchPointerEmulation::Merged);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 1, TouchPhase::Started, 40.0, 40.0);
+    touch(&mut platform, 1, TouchPhase::Moved, 60.0, 60.0);
+    touch(&mut platform, 0, TouchPhase::Ended, 10.0, 10.0);
+    touch(&mut platform, 1, TouchPhase::Ended, 60.0, 60.0);
+
+    assert_eq!(
+        pointer_events(&mut platform),
+        vec![
+            Event::PointerMoved(Pos2::new(5.0, 5.0)),
+            button(5.0, 5.0, true),
+            Event::PointerMoved(Pos2::new(30.0, 30.0)),
+            button(30.0, 30.0, false),
+            Event::PointerGone,
+        ]
+    );
+}
+
+#[test]
+fn first_finger_ignores_other_fingers() {
+    let mut platform = platform(TouchPointerEmulation::FirstFinger);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 1, TouchPhase::Started, 40.0, 40.0);
+    touch(&mut platform, 1, TouchPhase::Moved, 60.0, 60.0);
+    touch(&mut platform, 0, TouchPhase::Moved, 20.0, 20.0);
+    touch(&mut platform, 0, TouchPhase::Ended, 20.0, 20.0);
+    touch(&mut platform, 1, TouchPhase::Ended, 60.0, 60.0);
+
+    assert_eq!(
+        pointer_events(&mut platform),
+        vec![
+            Event::PointerMoved(Pos2::new(5.0, 5.0)),
+            button(5.0, 5.0, true),
+            Event::PointerMoved(Pos2::new(10.0, 10.0)),
+            button(10.0, 10.0, false),
+            Event::PointerGone,
+        ]
+    );
+}
+
+#[test]
+fn first_finger_can_be_followed_by_another_finger() {
+    let mut platform = platform(TouchPointerEmulation::FirstFinger);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 0, TouchPhase::Cancelled, 10.0, 10.0);
+    touch(&mut platform, 1, TouchPhase::Started, 40.0, 40.0);
+
+    assert_eq!(
+        pointer_events(&mut platform),
+        vec![
+            Event::PointerMoved(Pos2::new(5.0, 5.0)),
+            button(5.0, 5.0, true),
+            button(5.0, 5.0, false),
+            Event::PointerGone,
+            Event::PointerMoved(Pos2::new(20.0, 20.0)),
+            button(20.0, 20.0, true),
+        ]
+    );
+}
+
+#[test]
+fn disabled_emulation_only_sends_touches() {
+    let mut platform = platform(TouchPointerEmulation::Disabled);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 0, TouchPhase::Moved, 20.0, 20.0);
+    touch(&mut platform, 0, TouchPhase::Ended, 20.0, 20.0);
+
+    assert_eq!(platform.raw_input_mut().events.len(), 3);
+    assert!(pointer_events(&mut platform).is_empty());
+}
+
+#[test]
+fn long_press_sends_secondary_click() {
+    let mut platform = gesture_platform();
+    platform.update_time(1.0);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 0, TouchPhase::Moved, 14.0, 10.0);
+    frame_events(&mut platform);
+
+    platform.update_time(2.0);
+    let secondary: Vec<_> = frame_events(&mut platform)
+        .into_iter()
+        .filter(|event| {
+            matches!(
+                event,
+                Event::PointerButton {
+                    button: PointerButton::Secondary,
+                    ..
+                }
+            )
+        })
+        .collect();
+    assert_eq!(secondary.len(), 2);
+}
+
+#[test]
+fn click_is_no_long_press() {
+    let mut platform = gesture_platform();
+    let max_click_duration = platform
+        .context()
+        .options(|options| options.input_options.max_click_duration);
+    platform.update_time(1.0);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    frame_events(&mut platform);
+
+    // Egui still counts lifting the finger now as a click.
+    platform.update_time(1.0 + max_click_duration);
+    assert!(!frame_events(&mut platform).iter().any(|event| matches!(
+        event,
+        Event::PointerButton {
+            button: PointerButton::Secondary,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn moving_cancels_long_press() {
+    let mut platform = gesture_platform();
+    platform.update_time(1.0);
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 0, TouchPhase::Moved, 100.0, 10.0);
+    frame_events(&mut platform);
+
+    platform.update_time(2.0);
+    assert!(!frame_events(&mut platform).iter().any(|event| matches!(
+        event,
+        Event::PointerButton {
+            button: PointerButton::Secondary,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn two_finger_drag_scrolls() {
+    let mut platform = gesture_platform();
+    touch(&mut platform, 0, TouchPhase::Started, 10.0, 10.0);
+    touch(&mut platform, 1, TouchPhase::Started, 50.0, 10.0);
+    pointer_events(&mut platform);
+
+    touch(&mut platform, 0, TouchPhase::Moved, 10.0, 30.0);
+    touch(&mut platform, 1, TouchPhase::Moved, 50.0, 30.0);
+
+    assert_eq!(
+        pointer_events(&mut platform),
+        vec![
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Point,
+                delta: egui::vec2(0.0, 5.0),
+                modifiers: Default::default(),
+            },
+            Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Point,
+                delta: egui::vec2(0.0, 5.0),
+                modifiers: Default::default(),
+            },
+        ]
+    );
+}